@@ -0,0 +1,5 @@
+use webdriver_install::Driver;
+
+fn main() {
+    Driver::Edge.install().unwrap();
+}
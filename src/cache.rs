@@ -0,0 +1,232 @@
+//! Cache of downloaded driver archives, shared by all installation directories
+//!
+//! Archives are stored as `<cache dir>/<driver>/<version>/<platform>/<archive>`.
+use crate::{Driver, Error, Result};
+use tempfile::NamedTempFile;
+use tracing::debug;
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the default cache directory
+pub const CACHE_DIR_ENV: &str = "WEBDRIVER_INSTALL_CACHE_DIR";
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// A cached driver archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub driver: Driver,
+    pub version: String,
+    pub platform: String,
+    pub path: PathBuf,
+}
+
+impl Cache {
+    /// Returns the default cache, `webdriver-install` in the user's cache directory
+    ///
+    /// That's `$XDG_CACHE_HOME/webdriver-install` or `$HOME/.cache/webdriver-install`
+    /// on Linux, for example. It can be overridden with `WEBDRIVER_INSTALL_CACHE_DIR`.
+    pub fn new() -> Result<Self> {
+        if let Some(dir) = std::env::var_os(CACHE_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Ok(Self::at(dir));
+        }
+        let cache_dir = dirs::cache_dir().ok_or(Error::DirectoryNotFound("cache"))?;
+        Ok(Self::at(cache_dir.join("webdriver-install")))
+    }
+
+    /// Returns a cache stored in `dir`
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path an archive is cached at
+    pub(crate) fn archive_path(
+        &self,
+        driver: Driver,
+        version: &str,
+        platform: &str,
+        archive_filename: &str,
+    ) -> PathBuf {
+        self.archive_dir(driver, version, platform)
+            .join(archive_filename)
+    }
+
+    /// Returns the path of the cached archive of `version` of `driver` for `platform`,
+    /// if it was downloaded before
+    ///
+    /// Unlike [`Cache::archive_path`], the archive file name needn't be known, which
+    /// would take a request for some drivers.
+    pub(crate) fn find(
+        &self,
+        driver: Driver,
+        version: &str,
+        platform: &str,
+    ) -> Result<Option<PathBuf>> {
+        let dir = self.archive_dir(driver, version, platform);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && !is_temporary(&path) {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    fn archive_dir(&self, driver: Driver, version: &str, platform: &str) -> PathBuf {
+        self.dir.join(driver.as_str()).join(version).join(platform)
+    }
+
+    /// Creates a temporary file to download the archive cached at `path` to
+    ///
+    /// The archive is written to a temporary file first, so that concurrent
+    /// installations never read a partially written archive.
+    pub(crate) fn temp_file(&self, path: &Path) -> Result<NamedTempFile> {
+        // `path` always has a parent, see `Cache::archive_path`
+        let dir = path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(dir)?;
+        Ok(NamedTempFile::new_in(dir)?)
+    }
+
+    /// Moves the completely written `tmp_file` to `path`, which was returned by
+    /// [`Cache::archive_path`]
+    pub(crate) fn store(&self, tmp_file: NamedTempFile, path: &Path) -> Result<File> {
+        let file = tmp_file.persist(path).map_err(|e| e.error)?;
+        debug!("cached at {:?}", path);
+        Ok(file)
+    }
+
+    /// Returns all cached archives
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::cache::Cache;
+    ///
+    /// for entry in Cache::new()?.entries()? {
+    ///     println!("{} {} {}", entry.driver.as_str(), entry.version, entry.platform);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = vec![];
+        for driver in Driver::ALL {
+            for version_dir in sub_dirs(&self.dir.join(driver.as_str()))? {
+                for platform_dir in sub_dirs(&version_dir)? {
+                    for archive in fs::read_dir(&platform_dir)? {
+                        let path = archive?.path();
+                        if !path.is_file() || is_temporary(&path) {
+                            continue;
+                        }
+                        entries.push(CacheEntry {
+                            driver: *driver,
+                            version: file_name(&version_dir),
+                            platform: file_name(&platform_dir),
+                            path,
+                        });
+                    }
+                }
+            }
+        }
+        entries.sort_by(|a, b| {
+            (a.driver.as_str(), &a.platform, version_key(&a.version)).cmp(&(
+                b.driver.as_str(),
+                &b.platform,
+                version_key(&b.version),
+            ))
+        });
+        Ok(entries)
+    }
+
+    /// Removes all but the `keep` newest versions of each driver and platform
+    ///
+    /// Returns the removed entries.
+    pub fn prune(&self, keep: usize) -> Result<Vec<CacheEntry>> {
+        let entries = self.entries()?;
+        let mut removed = vec![];
+        for (i, entry) in entries.iter().enumerate() {
+            // `entries` is sorted by version, so all newer versions come after `entry`
+            let newer_versions = entries[i + 1..]
+                .iter()
+                .filter(|e| {
+                    e.driver == entry.driver
+                        && e.platform == entry.platform
+                        && e.version != entry.version
+                })
+                .map(|e| &e.version)
+                .collect::<HashSet<_>>()
+                .len();
+            if newer_versions >= keep {
+                fs::remove_file(&entry.path)?;
+                remove_empty_parents(&entry.path, &self.dir)?;
+                debug!("removed {:?} from cache", entry.path);
+                removed.push(entry.clone());
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn sub_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut dirs = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Temporary files are hidden, see [`Cache::store`]
+fn is_temporary(path: &Path) -> bool {
+    file_name(path).starts_with('.')
+}
+
+/// Removes the directories between `path` and `root` that became empty
+fn remove_empty_parents(path: &Path, root: &Path) -> Result<()> {
+    for dir in path.ancestors().skip(1).take_while(|dir| *dir != root) {
+        if fs::read_dir(dir)?.next().is_some() {
+            break;
+        }
+        fs::remove_dir(dir)?;
+    }
+    Ok(())
+}
+
+/// Returns a key to sort versions like `v0.33.0` or `118.0.5993.70` numerically
+pub(crate) fn version_key(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[test]
+fn version_key_test() {
+    assert!(version_key("118.0.5993.70") > version_key("118.0.5993.9"));
+    assert!(version_key("v0.33.0") > version_key("v0.4.0"));
+}
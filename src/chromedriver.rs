@@ -2,79 +2,520 @@
 /// based on the installed browser version.
 ///
 /// See https://chromedriver.chromium.org/downloads/version-selection
-use eyre::{eyre, Result};
 use regex::Regex;
-use tracing::{debug};
-use url::Url;
 use serde_json::Value;
+use tracing::debug;
+use url::Url;
 
+use std::fmt;
 use std::process::{Command, Stdio};
+use std::str::FromStr;
 
-use crate::DriverFetcher;
+use crate::http::HttpClient;
+use crate::platform::{Arch, Os, Platform};
+use crate::{mirror_url, DriverFetcher, Error, Result};
 
 #[cfg(target_os = "windows")]
 use crate::run_powershell_cmd;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Base URL of the Chrome for Testing JSON API endpoints
+///
+/// See https://github.com/GoogleChromeLabs/chrome-for-testing#json-api-endpoints
+const VERSIONS_BASE_URL: &str = "https://googlechromelabs.github.io/chrome-for-testing";
+const VERSIONS_MIRROR_ENV: &str = "WEBDRIVER_INSTALL_CHROMEDRIVER_VERSIONS_MIRROR";
 
-pub struct Chromedriver;
+/// Base URL of the chromedriver storage for Chrome versions before Chrome for Testing
+///
+/// See https://chromedriver.chromium.org/downloads/version-selection
+const LEGACY_BASE_URL: &str = "https://chromedriver.storage.googleapis.com";
+const LEGACY_MIRROR_ENV: &str = "WEBDRIVER_INSTALL_CHROMEDRIVER_LEGACY_MIRROR";
+
+/// The first major version with a chromedriver in Chrome for Testing
+const FIRST_CFT_MAJOR: i16 = 115;
+
+const KNOWN_GOOD_VERSIONS: &str = "known-good-versions-with-downloads.json";
+const LAST_KNOWN_GOOD_VERSIONS: &str = "last-known-good-versions-with-downloads.json";
+const LATEST_PATCH_VERSIONS_PER_BUILD: &str = "latest-patch-versions-per-build-with-downloads.json";
+const LATEST_VERSIONS_PER_MILESTONE: &str = "latest-versions-per-milestone-with-downloads.json";
+
+/// Chrome for Testing release channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Channel {
+    #[default]
+    Stable,
+    Beta,
+    Dev,
+    Canary,
+}
+
+impl Channel {
+    pub const ALL: [Self; 4] = [Self::Stable, Self::Beta, Self::Dev, Self::Canary];
+
+    /// Returns the name of the channel in the Chrome for Testing JSON
+    fn json_key(&self) -> &'static str {
+        match self {
+            Self::Stable => "Stable",
+            Self::Beta => "Beta",
+            Self::Dev => "Dev",
+            Self::Canary => "Canary",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json_key().to_ascii_lowercase())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|channel| channel.json_key().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::InvalidChannel(s.to_string()))
+    }
+}
+
+pub struct Chromedriver {
+    base_url: String,
+    versions_url: String,
+    legacy_url: String,
+    channel: Channel,
+    platform: Option<Platform>,
+    http: HttpClient,
+}
 
 impl DriverFetcher for Chromedriver {
     const BASE_URL: &'static str = "https://storage.googleapis.com/chrome-for-testing-public";
+    const MIRROR_ENV: &'static str = "WEBDRIVER_INSTALL_CHROMEDRIVER_MIRROR";
 
-    /// Returns the latest version of the driver
+    /// Returns the latest version of the driver in the channel
     fn latest_version(&self) -> Result<String> {
-        const VERSION_URL: &'static str = "https://googlechromelabs.github.io/chrome-for-testing/known-good-versions-with-downloads.json";
-        let version_response = reqwest::blocking::get(VERSION_URL)?;
-        let data: Value = version_response.json()?;
-
-        // Extract the last element from the `versions` array and get the `version` field
-        if let Some(last_version) = data["versions"].as_array().and_then(|v| v.last()) {
-            if let Some(version) = last_version["version"].as_str() {
-                debug!("Latest version: {}", version);
-                return Ok(version.to_string());
-            }
-        }
+        let platform = self.platform_name()?;
+        channel_version(
+            &self.versions_json(LAST_KNOWN_GOOD_VERSIONS)?,
+            self.channel,
+            platform,
+        )
+    }
 
-        Err(eyre!("Could not find the latest version"))
+    #[cfg(feature = "async")]
+    async fn latest_version_async(&self) -> Result<String> {
+        let platform = self.platform_name()?;
+        channel_version(
+            &self.versions_json_async(LAST_KNOWN_GOOD_VERSIONS).await?,
+            self.channel,
+            platform,
+        )
     }
 
-    /// Returns the download url for the driver executable
+    /// Returns the download url for the driver executable, following the URL scheme
+    /// of the Chrome for Testing storage
+    ///
+    /// See [`Chromedriver::download_url`] for the URL listed in the JSON API.
     fn direct_download_url(&self, version: &str) -> Result<Url> {
         Ok(Url::parse(&format!(
             "{}/{version}/{platform}/chromedriver-{platform}.zip",
-            Self::BASE_URL,
+            self.base_url,
             version = version,
-            platform = Self::platform()?
+            platform = self.platform_name()?
         ))?)
     }
+
+    fn available_versions(&self, platform_only: bool) -> Result<Vec<String>> {
+        let platform = if platform_only {
+            Some(self.platform_name()?)
+        } else {
+            None
+        };
+        Ok(known_good_versions(
+            &self.versions_json(KNOWN_GOOD_VERSIONS)?,
+            platform,
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    async fn available_versions_async(&self, platform_only: bool) -> Result<Vec<String>> {
+        let platform = if platform_only {
+            Some(self.platform_name()?)
+        } else {
+            None
+        };
+        Ok(known_good_versions(
+            &self.versions_json_async(KNOWN_GOOD_VERSIONS).await?,
+            platform,
+        ))
+    }
 }
 
 impl Chromedriver {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            base_url: mirror_url(Self::MIRROR_ENV, Self::BASE_URL),
+            versions_url: mirror_url(VERSIONS_MIRROR_ENV, VERSIONS_BASE_URL),
+            legacy_url: mirror_url(LEGACY_MIRROR_ENV, LEGACY_BASE_URL),
+            channel: Channel::default(),
+            platform: None,
+            http: HttpClient::new(),
+        }
+    }
+
+    /// Downloads the driver for `platform` instead of the host platform
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Resolves the latest version in `channel` instead of the stable one
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Sends the requests with `client` instead of the default one
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Downloads the driver from `url` instead of the Chrome for Testing storage
+    pub fn mirror(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Fetches the Chrome for Testing JSON API endpoints from `url`
+    pub fn versions_mirror(mut self, url: &str) -> Self {
+        self.versions_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Downloads drivers for Chrome before version 115 from `url` instead of
+    /// `chromedriver.storage.googleapis.com`
+    pub fn legacy_mirror(mut self, url: &str) -> Self {
+        self.legacy_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Returns the download url for the driver executable listed in the Chrome for Testing JSON
+    ///
+    /// Only the latest patch version of each build is listed in the JSON fetched for
+    /// this, the URL of older patch versions follows the URL scheme of the storage, see
+    /// [`DriverFetcher::direct_download_url`]. Versions before Chrome for Testing are
+    /// downloaded from the legacy storage.
+    pub fn download_url(&self, version: &str) -> Result<Url> {
+        if is_legacy(version) {
+            return self.legacy_download_url(version);
+        }
+        let platform = self.platform_name()?;
+        let data = self.versions_json(LATEST_PATCH_VERSIONS_PER_BUILD)?;
+        self.listed_download_url(&data, version, platform)
+    }
+
+    /// Async variant of [`Chromedriver::download_url`]
+    #[cfg(feature = "async")]
+    pub async fn download_url_async(&self, version: &str) -> Result<Url> {
+        if is_legacy(version) {
+            return self.legacy_download_url(version);
+        }
+        let platform = self.platform_name()?;
+        let data = self
+            .versions_json_async(LATEST_PATCH_VERSIONS_PER_BUILD)
+            .await?;
+        self.listed_download_url(&data, version, platform)
+    }
+
+    /// Returns the download url of `version` in the `latest-patch-versions-per-build` JSON,
+    /// or the one following the URL scheme of the storage if it isn't listed there
+    fn listed_download_url(&self, data: &Value, version: &str, platform: &str) -> Result<Url> {
+        let build = version.rsplit_once('.').map_or(version, |(build, _)| build);
+        match listed_release(data, "builds", build, platform) {
+            Some((listed, url)) if listed == version => self.mirrored(url),
+            _ => self.direct_download_url(version),
+        }
+    }
+
+    /// Returns the download url of a driver from the legacy storage
+    fn legacy_download_url(&self, version: &str) -> Result<Url> {
+        Ok(Url::parse(&format!(
+            "{}/{}/chromedriver_{}.zip",
+            self.legacy_url,
+            version,
+            self.legacy_platform_name()?
+        ))?)
+    }
+
+    /// Returns the URL of the file with the latest legacy driver version for the browser's build
+    fn legacy_release_url(&self, browser_version: &Version) -> String {
+        format!(
+            "{}/LATEST_RELEASE_{}",
+            self.legacy_url,
+            browser_version.build_version()
+        )
+    }
+
+    /// Points a download URL of the Chrome for Testing storage to the mirror, if one is set
+    fn mirrored(&self, url: &str) -> Result<Url> {
+        let url = match url.strip_prefix(Self::BASE_URL) {
+            Some(path) => format!("{}{}", self.base_url, path),
+            None => url.to_string(),
+        };
+        Ok(Url::parse(&url)?)
+    }
+
+    /// Fetches one of the Chrome for Testing JSON API endpoints
+    fn versions_json(&self, file_name: &str) -> Result<Value> {
+        let url = format!("{}/{}", self.versions_url, file_name);
+        Ok(self.http.get(&url)?.json()?)
+    }
+
+    /// Async variant of [`Chromedriver::versions_json`]
+    #[cfg(feature = "async")]
+    async fn versions_json_async(&self, file_name: &str) -> Result<Value> {
+        let url = format!("{}/{}", self.versions_url, file_name);
+        let resp = self.http.get_async(&url).await?;
+        self.http.read(resp.json()).await
+    }
+
+    /// Returns the driver version matching the installed Chrome/Chromium browser
+    ///
+    /// Falls back to the latest driver version if no browser could be found. The
+    /// download url is returned as well if it was listed in the JSON fetched for the
+    /// version, so that it needn't be fetched again.
+    pub fn matching_release(&self) -> Result<(String, Option<Url>)> {
+        match Version::find() {
+            Ok(browser_version) => self.release_for(&browser_version),
+            Err(e) => {
+                debug!("{}, falling back to the latest chromedriver", e);
+                Ok((self.latest_version()?, None))
+            }
+        }
+    }
+
+    /// Returns the driver version to be used with the given browser version
+    ///
+    /// Prefers the latest patch release of the exact `major.minor.build` and falls
+    /// back to the latest release of the same major version (milestone), unless they
+    /// have no chromedriver for the platform.
+    ///
+    /// Chrome before version 115 isn't covered by Chrome for Testing, its driver is
+    /// looked up by build in the legacy storage. The download url is only returned for
+    /// Chrome for Testing drivers.
+    pub fn release_for(&self, browser_version: &Version) -> Result<(String, Option<Url>)> {
+        if browser_version.major() < FIRST_CFT_MAJOR {
+            let resp = self.http.get(&self.legacy_release_url(browser_version))?;
+            return Ok((legacy_version(&resp.text()?)?, None));
+        }
+        let data = self.versions_json(LATEST_PATCH_VERSIONS_PER_BUILD)?;
+        if let Some(release) = self.build_release(&data, browser_version)? {
+            return Ok(release);
+        }
+        let data = self.versions_json(LATEST_VERSIONS_PER_MILESTONE)?;
+        self.milestone_release(&data, browser_version)
+    }
+
+    /// Async variant of [`Chromedriver::matching_release`]
+    #[cfg(feature = "async")]
+    pub async fn matching_release_async(&self) -> Result<(String, Option<Url>)> {
+        match Version::find() {
+            Ok(browser_version) => self.release_for_async(&browser_version).await,
+            Err(e) => {
+                debug!("{}, falling back to the latest chromedriver", e);
+                Ok((self.latest_version_async().await?, None))
+            }
+        }
+    }
+
+    /// Async variant of [`Chromedriver::release_for`]
+    #[cfg(feature = "async")]
+    pub async fn release_for_async(
+        &self,
+        browser_version: &Version,
+    ) -> Result<(String, Option<Url>)> {
+        if browser_version.major() < FIRST_CFT_MAJOR {
+            let resp = self
+                .http
+                .get_async(&self.legacy_release_url(browser_version))
+                .await?;
+            let version = legacy_version(&self.http.read(resp.text()).await?)?;
+            return Ok((version, None));
+        }
+        let data = self
+            .versions_json_async(LATEST_PATCH_VERSIONS_PER_BUILD)
+            .await?;
+        if let Some(release) = self.build_release(&data, browser_version)? {
+            return Ok(release);
+        }
+        let data = self
+            .versions_json_async(LATEST_VERSIONS_PER_MILESTONE)
+            .await?;
+        self.milestone_release(&data, browser_version)
+    }
+
+    /// Returns the latest patch release of the browser's build and its download url,
+    /// listed in the `latest-patch-versions-per-build` JSON
+    fn build_release(
+        &self,
+        data: &Value,
+        browser_version: &Version,
+    ) -> Result<Option<(String, Option<Url>)>> {
+        let build = browser_version.build_version();
+        match listed_release(data, "builds", &build, self.platform_name()?) {
+            Some((version, url)) => {
+                debug!("Found chromedriver {} by build", version);
+                Ok(Some((version.to_string(), Some(self.mirrored(url)?))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the latest release of the browser's milestone and its download url,
+    /// listed in the `latest-versions-per-milestone` JSON
+    fn milestone_release(
+        &self,
+        data: &Value,
+        browser_version: &Version,
+    ) -> Result<(String, Option<Url>)> {
+        let milestone = browser_version.major().to_string();
+        let (version, url) = listed_release(data, "milestones", &milestone, self.platform_name()?)
+            .ok_or_else(|| version_not_found(browser_version))?;
+        debug!("Found chromedriver {} by milestone", version);
+        Ok((version.to_string(), Some(self.mirrored(url)?)))
     }
 
     /// Returns the platform part to be used in the download URL
     ///
-    /// The `match` is based on the file contents of, for example
-    /// https://chromedriver.storage.googleapis.com/index.html?path=72.0.3626.69/
+    /// The `match` is based on the platforms listed in
+    /// https://googlechromelabs.github.io/chrome-for-testing/known-good-versions-with-downloads.json
+    fn platform_name(&self) -> Result<&'static str> {
+        let platform = self.platform.map_or_else(Platform::host, Ok)?;
+        match (platform.os, platform.arch) {
+            (Os::Linux, Arch::X64) => Ok("linux64"),
+            (Os::Mac, Arch::X64) => Ok("mac-x64"),
+            (Os::Mac, Arch::Arm64) => Ok("mac-arm64"),
+            (Os::Windows, Arch::X64) => Ok("win64"),
+            (Os::Windows, Arch::X86) => Ok("win32"),
+            _ => Err(platform.unsupported("chromedriver")),
+        }
+    }
+
+    /// Returns the platform part to be used in legacy download URLs
     ///
-    /// If future chromedriver releases have multiple pointer widths per platform,
-    /// we have to change this to work like `Geckodriver::platform`.
-    fn platform() -> Result<String> {
-        match std::env::consts::OS {
-            "linux" => Ok(String::from("linux64")),
-            "macos" => Ok(String::from("mac-x64")),
-            "windows" => Ok(String::from("win32")),
-            other => Err(eyre!(
-                "webdriver-install doesn't support '{}' currently",
-                other
-            )),
+    /// There were only 32-bit builds for Windows, which run on 64-bit Windows as well.
+    fn legacy_platform_name(&self) -> Result<&'static str> {
+        let platform = self.platform.map_or_else(Platform::host, Ok)?;
+        match (platform.os, platform.arch) {
+            (Os::Linux, Arch::X64) => Ok("linux64"),
+            (Os::Mac, Arch::X64) => Ok("mac64"),
+            (Os::Mac, Arch::Arm64) => Ok("mac_arm64"),
+            (Os::Windows, Arch::X64 | Arch::X86) => Ok("win32"),
+            _ => Err(platform.unsupported("chromedriver")),
         }
     }
 }
 
+/// Returns whether `version` is older than Chrome for Testing
+fn is_legacy(version: &str) -> bool {
+    version
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<i16>().ok())
+        .is_some_and(|major| major < FIRST_CFT_MAJOR)
+}
+
+/// Parses the contents of a legacy `LATEST_RELEASE_<build>` file
+fn legacy_version(content: &str) -> Result<String> {
+    let version = content.trim();
+    if version.is_empty() {
+        return Err(Error::VersionParse(String::from(
+            "Could not find a legacy chromedriver version",
+        )));
+    }
+    debug!("Found legacy chromedriver {} by build", version);
+    Ok(version.to_string())
+}
+
+/// Returns the version of `channel` in the `last-known-good-versions` JSON
+///
+/// Fails if the version has no chromedriver for `platform`.
+fn channel_version(data: &Value, channel: Channel, platform: &str) -> Result<String> {
+    let entry = &data["channels"][channel.json_key()];
+    let version = entry["version"].as_str().ok_or_else(|| {
+        Error::VersionNotFound(format!(
+            "Could not find the latest chromedriver version in the {} channel",
+            channel
+        ))
+    })?;
+    if chromedriver_url(entry, platform).is_none() {
+        return Err(Error::VersionNotFound(format!(
+            "No chromedriver {} for {} in the {} channel",
+            version, platform, channel
+        )));
+    }
+    debug!("Latest {} version: {}", channel, version);
+    Ok(version.to_string())
+}
+
+/// Returns the chromedriver download URL for `platform` of a version in the JSON API
+fn chromedriver_url<'a>(entry: &'a Value, platform: &str) -> Option<&'a str> {
+    entry["downloads"]["chromedriver"]
+        .as_array()?
+        .iter()
+        .find(|download| download["platform"] == platform)
+        .and_then(|download| download["url"].as_str())
+}
+
+/// Returns the version listed for `key` in the `group` of builds or milestones, and
+/// its chromedriver download URL for `platform`
+///
+/// Returns `None` if there is no chromedriver for `platform`.
+fn listed_release<'a>(
+    data: &'a Value,
+    group: &str,
+    key: &str,
+    platform: &str,
+) -> Option<(&'a str, &'a str)> {
+    let entry = &data[group][key];
+    Some((
+        entry["version"].as_str()?,
+        chromedriver_url(entry, platform)?,
+    ))
+}
+
+/// Returns the versions in the `known-good-versions` JSON with a chromedriver download
+///
+/// Only versions with a download for `platform` are returned, if given.
+fn known_good_versions(data: &Value, platform: Option<&str>) -> Vec<String> {
+    let versions = data["versions"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    versions
+        .iter()
+        .filter(|version| {
+            let downloads = version["downloads"]["chromedriver"].as_array();
+            downloads.is_some_and(|downloads| match platform {
+                Some(platform) => downloads.iter().any(|d| d["platform"] == platform),
+                None => !downloads.is_empty(),
+            })
+        })
+        .filter_map(|version| version["version"].as_str())
+        .map(String::from)
+        .collect()
+}
+
+fn version_not_found(browser_version: &Version) -> Error {
+    Error::VersionNotFound(format!(
+        "Could not find a chromedriver for Chrome {}",
+        browser_version.full_version()
+    ))
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Version {
     major: i16,
@@ -86,7 +527,7 @@ pub struct Version {
 struct Location {}
 
 #[cfg(target_os = "linux")]
-static LINUX_CHROME_DIRS: &[&'static str] = &[
+static LINUX_CHROME_DIRS: &[&str] = &[
     "/usr/local/sbin",
     "/usr/local/bin",
     "/usr/sbin",
@@ -96,34 +537,43 @@ static LINUX_CHROME_DIRS: &[&'static str] = &[
     "/opt/google/chrome",
 ];
 #[cfg(target_os = "linux")]
-static LINUX_CHROME_FILES: &[&'static str] =
-    &["google-chrome", "chrome", "chromium", "chromium-browser"];
+static LINUX_CHROME_FILES: &[&str] = &["google-chrome", "chrome", "chromium", "chromium-browser"];
 
 #[cfg(target_os = "windows")]
-static WIN_CHROME_DIRS: &[&'static str] = &["Google\\Chrome\\Application", "Chromium\\Application"];
+static WIN_CHROME_DIRS: &[&str] = &["Google\\Chrome\\Application", "Chromium\\Application"];
 
 #[cfg(target_os = "macos")]
-static MAC_CHROME_DIRS: &[&'static str] = &[
+static MAC_CHROME_DIRS: &[&str] = &[
     "/Applications/Chromium.app",
     "/Applications/Google Chrome.app",
 ];
 #[cfg(target_os = "macos")]
-static MAC_CHROME_FILES: &[&'static str] =
-    &["Contents/MacOS/Chromium", "Contents/MacOS/Google Chrome"];
+static MAC_CHROME_FILES: &[&str] = &["Contents/MacOS/Chromium", "Contents/MacOS/Google Chrome"];
 
 impl Version {
     /// Returns the version of the currently installed Chrome/Chromium browser
     pub fn find() -> Result<Self> {
+        Self::from_executable(&Location::location()?)
+    }
+
+    /// Returns the version of the browser executable at `path`
+    ///
+    /// Works for all Chromium based browsers, as they share the version format.
+    pub(crate) fn from_executable(path: &Path) -> Result<Self> {
         #[cfg(target_os = "linux")]
-        return Self::linux_version();
+        return Self::linux_version(path);
         #[cfg(target_os = "windows")]
-        return Self::windows_version();
+        return Self::windows_version(path);
         #[cfg(target_os = "macos")]
-        return Self::mac_version();
+        return Self::mac_version(path);
+    }
+
+    /// Returns the major version
+    pub fn major(&self) -> i16 {
+        self.major
     }
 
     /// Returns major.minor.build.patch
-    #[allow(dead_code)]
     pub fn full_version(&self) -> String {
         format!(
             "{}.{}.{}.{}",
@@ -137,59 +587,62 @@ impl Version {
     }
 
     #[cfg(target_os = "linux")]
-    fn linux_version() -> Result<Self> {
+    fn linux_version(path: &Path) -> Result<Self> {
         // TODO: WSL?
-        let output = Command::new(Location::location()?)
+        let output = Command::new(path)
             .arg("--version")
             .stdout(Stdio::piped())
             .output()?
             .stdout;
 
-        let output = String::from_utf8(output)?;
-        debug!("Chrome --version output: {}", output);
+        let output = String::from_utf8(output).map_err(|e| Error::VersionParse(e.to_string()))?;
+        debug!("{} --version output: {}", path.display(), output);
 
-        Ok(Self::version_from_output(&output)?)
+        Self::version_from_output(&output)
     }
 
     #[cfg(target_os = "windows")]
-    fn windows_version() -> Result<Self> {
+    fn windows_version(path: &Path) -> Result<Self> {
         let output = run_powershell_cmd(&format!(
             "(Get-ItemProperty '{}').VersionInfo.ProductVersion",
-            Location::location()?.display()
+            path.display()
         ));
 
-        let stdout = String::from_utf8(output.stdout)?;
-        debug!("chrome version: {}", stdout);
+        let stdout =
+            String::from_utf8(output.stdout).map_err(|e| Error::VersionParse(e.to_string()))?;
+        debug!("{} version: {}", path.display(), stdout);
 
-        Ok(Self::version_from_output(&stdout)?)
+        Self::version_from_output(&stdout)
     }
 
     #[cfg(target_os = "macos")]
-    fn mac_version() -> Result<Self> {
-        let output = Command::new(Location::location()?)
+    fn mac_version(path: &Path) -> Result<Self> {
+        let output = Command::new(path)
             .arg("--version")
             .stdout(Stdio::piped())
             .output()?
             .stdout;
 
-        let output = String::from_utf8(output)?;
-        debug!("Chrome --version output: {}", output);
+        let output = String::from_utf8(output).map_err(|e| Error::VersionParse(e.to_string()))?;
+        debug!("{} --version output: {}", path.display(), output);
 
-        Ok(Self::version_from_output(&output)?)
+        Self::version_from_output(&output)
     }
 
     fn version_from_output(output: &str) -> Result<Self> {
-        let version_pattern = Regex::new(r"\d+\.\d+\.\d+\.\d+")?;
+        let version_pattern = Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap();
         let version = version_pattern
-            .captures(&output)
-            .ok_or(eyre!(
-                "regex: Could not find 4-part Chrome version string in '{}'",
-                output
-            ))?
+            .captures(output)
+            .ok_or_else(|| {
+                Error::VersionParse(format!(
+                    "regex: Could not find 4-part Chrome version string in '{}'",
+                    output
+                ))
+            })?
             .get(0)
             .map_or("", |m| m.as_str());
         let parts: Vec<i16> = version
-            .split(".")
+            .split('.')
             .map(|i| i.parse::<i16>().unwrap())
             .collect();
 
@@ -216,7 +669,7 @@ impl Location {
     #[cfg(target_os = "linux")]
     fn linux_location() -> Result<PathBuf> {
         // TODO: WSL?
-        for dir in LINUX_CHROME_DIRS.into_iter().map(PathBuf::from) {
+        for dir in LINUX_CHROME_DIRS.iter().map(PathBuf::from) {
             for file in LINUX_CHROME_FILES {
                 let path = dir.join(file);
                 if path.exists() {
@@ -224,7 +677,7 @@ impl Location {
                 }
             }
         }
-        Err(eyre!("Unable to find chrome executable"))
+        Err(Error::BrowserNotFound("chrome"))
     }
 
     #[cfg(target_os = "windows")]
@@ -239,7 +692,7 @@ impl Location {
         .into_iter()
         .flatten()
         .collect::<Vec<PathBuf>>();
-        for dir in WIN_CHROME_DIRS.into_iter().map(PathBuf::from) {
+        for dir in WIN_CHROME_DIRS.iter().map(PathBuf::from) {
             for root in &roots {
                 let path = root.join(&dir).join("chrome.exe");
                 debug!("root: {}", root.display());
@@ -249,12 +702,12 @@ impl Location {
                 }
             }
         }
-        Err(eyre!("Unable to find chrome executable"))
+        Err(Error::BrowserNotFound("chrome"))
     }
 
     #[cfg(target_os = "macos")]
     fn mac_location() -> Result<PathBuf> {
-        for dir in MAC_CHROME_DIRS.into_iter().map(PathBuf::from) {
+        for dir in MAC_CHROME_DIRS.iter().map(PathBuf::from) {
             for file in MAC_CHROME_FILES {
                 let path = dir.join(file);
                 if path.exists() {
@@ -262,7 +715,7 @@ impl Location {
                 }
             }
         }
-        Err(eyre!("Unable to find chrome executable"))
+        Err(Error::BrowserNotFound("chrome"))
     }
 }
 
@@ -300,9 +753,227 @@ fn version_from_output_panic_not_4_parts_test() {
     Version::version_from_output("abc 1.0.1 def").unwrap();
 }
 
+#[test]
+fn listed_release_test() {
+    let data: Value = serde_json::from_str(
+        r#"{"builds": {
+            "115.0.5763": {"version": "115.0.5763.0", "downloads": {"chromedriver": [
+                {"platform": "win64", "url": "https://example.com/115/win64/chromedriver-win64.zip"}
+            ]}},
+            "118.0.5993": {"version": "118.0.5993.70", "downloads": {"chromedriver": [
+                {"platform": "linux64", "url": "https://example.com/118/linux64/chromedriver-linux64.zip"}
+            ]}}
+        }}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        Some((
+            "118.0.5993.70",
+            "https://example.com/118/linux64/chromedriver-linux64.zip"
+        )),
+        listed_release(&data, "builds", "118.0.5993", "linux64")
+    );
+    assert_eq!(
+        None,
+        listed_release(&data, "builds", "115.0.5763", "linux64")
+    );
+    assert_eq!(None, listed_release(&data, "builds", "116.0.5845", "win64"));
+    assert_eq!(None, listed_release(&data, "milestones", "118", "linux64"));
+}
+
+#[test]
+fn build_and_milestone_release_test() {
+    let builds: Value = serde_json::from_str(
+        r#"{"builds": {"118.0.5993": {"version": "118.0.5993.70", "downloads": {"chromedriver": [
+            {"platform": "linux64", "url": "https://example.com/118.0.5993.70/chromedriver-linux64.zip"}
+        ]}}}}"#,
+    )
+    .unwrap();
+    let milestones: Value = serde_json::from_str(
+        r#"{"milestones": {"118": {"version": "118.0.5994.0", "downloads": {"chromedriver": [
+            {"platform": "linux64", "url": "https://example.com/118.0.5994.0/chromedriver-linux64.zip"}
+        ]}}}}"#,
+    )
+    .unwrap();
+    let chromedriver = Chromedriver::new().platform(Platform::new(Os::Linux, Arch::X64));
+    let browser_version = |version: &str| Version::version_from_output(version).unwrap();
+
+    assert_eq!(
+        Some((
+            String::from("118.0.5993.70"),
+            Some(Url::parse("https://example.com/118.0.5993.70/chromedriver-linux64.zip").unwrap())
+        )),
+        chromedriver
+            .build_release(&builds, &browser_version("118.0.5993.11"))
+            .unwrap()
+    );
+    assert_eq!(
+        None,
+        chromedriver
+            .build_release(&builds, &browser_version("118.0.5992.0"))
+            .unwrap()
+    );
+    assert_eq!(
+        (
+            String::from("118.0.5994.0"),
+            Some(Url::parse("https://example.com/118.0.5994.0/chromedriver-linux64.zip").unwrap())
+        ),
+        chromedriver
+            .milestone_release(&milestones, &browser_version("118.0.5992.0"))
+            .unwrap()
+    );
+    assert!(matches!(
+        chromedriver.milestone_release(&milestones, &browser_version("117.0.5938.0")),
+        Err(Error::VersionNotFound(_))
+    ));
+}
+
+#[test]
+fn mirrored_test() {
+    let url = "https://storage.googleapis.com/chrome-for-testing-public/118.0.5993.70/linux64/chromedriver-linux64.zip";
+    assert_eq!(url, Chromedriver::new().mirrored(url).unwrap().as_str());
+    assert_eq!(
+        "https://mirror.example.com/cft/118.0.5993.70/linux64/chromedriver-linux64.zip",
+        Chromedriver::new()
+            .mirror("https://mirror.example.com/cft/")
+            .mirrored(url)
+            .unwrap()
+            .as_str()
+    );
+}
+
+#[test]
+fn channel_version_test() {
+    let data: Value = serde_json::from_str(
+        r#"{"channels": {
+            "Stable": {"channel": "Stable", "version": "118.0.5993.70", "downloads": {
+                "chromedriver": [{"platform": "linux64", "url": "https://example.com/linux64.zip"}]
+            }},
+            "Canary": {"channel": "Canary", "version": "120.0.6062.0", "downloads": {
+                "chromedriver": [{"platform": "win64", "url": "https://example.com/win64.zip"}]
+            }}
+        }}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        "118.0.5993.70",
+        channel_version(&data, Channel::default(), "linux64").unwrap()
+    );
+    assert_eq!(
+        "120.0.6062.0",
+        channel_version(&data, Channel::Canary, "win64").unwrap()
+    );
+    assert!(matches!(
+        channel_version(&data, Channel::Canary, "linux64"),
+        Err(Error::VersionNotFound(_))
+    ));
+    assert!(matches!(
+        channel_version(&data, Channel::Beta, "linux64"),
+        Err(Error::VersionNotFound(_))
+    ));
+}
+
+#[test]
+fn channel_from_str_test() {
+    for channel in Channel::ALL {
+        assert_eq!(channel, channel.to_string().parse().unwrap());
+    }
+    assert_eq!(Channel::Beta, "Beta".parse().unwrap());
+    assert!("nightly".parse::<Channel>().is_err());
+}
+
+#[test]
+fn known_good_versions_test() {
+    let data: Value = serde_json::from_str(
+        r#"{"versions": [
+            {"version": "113.0.5672.0", "downloads": {"chrome": []}},
+            {"version": "115.0.5763.0", "downloads": {"chromedriver": [{"platform": "win64"}]}},
+            {"version": "118.0.5993.70", "downloads": {"chromedriver": [
+                {"platform": "linux64"}, {"platform": "win64"}
+            ]}}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec!["115.0.5763.0", "118.0.5993.70"],
+        known_good_versions(&data, None)
+    );
+    assert_eq!(
+        vec!["118.0.5993.70"],
+        known_good_versions(&data, Some("linux64"))
+    );
+}
+
+#[test]
+fn is_legacy_test() {
+    assert!(is_legacy("114.0.5735.90"));
+    assert!(is_legacy("2.46"));
+    assert!(!is_legacy("115.0.5790.170"));
+    assert!(!is_legacy("latest"));
+}
+
+#[test]
+fn legacy_version_test() {
+    assert_eq!("114.0.5735.90", legacy_version("114.0.5735.90\n").unwrap());
+    assert!(matches!(legacy_version(""), Err(Error::VersionParse(_))));
+}
+
+#[test]
+fn legacy_download_url_test() {
+    let chromedriver = |os, arch| Chromedriver::new().platform(Platform::new(os, arch));
+    assert_eq!(
+        "https://chromedriver.storage.googleapis.com/114.0.5735.90/chromedriver_mac_arm64.zip",
+        chromedriver(Os::Mac, Arch::Arm64)
+            .download_url("114.0.5735.90")
+            .unwrap()
+            .as_str()
+    );
+    assert_eq!(
+        "https://chromedriver.storage.googleapis.com/114.0.5735.90/chromedriver_win32.zip",
+        chromedriver(Os::Windows, Arch::X64)
+            .download_url("114.0.5735.90")
+            .unwrap()
+            .as_str()
+    );
+    assert_eq!(
+        "https://chromedriver.storage.googleapis.com/LATEST_RELEASE_114.0.5735",
+        Chromedriver::new().legacy_release_url(&Version {
+            major: 114,
+            minor: 0,
+            build: 5735,
+            patch: 90
+        })
+    );
+    assert!(chromedriver(Os::Linux, Arch::Arm64)
+        .download_url("114.0.5735.90")
+        .is_err());
+}
+
+#[test]
+fn platform_name_test() {
+    let platform_name = |os, arch| {
+        Chromedriver {
+            platform: Some(Platform::new(os, arch)),
+            ..Chromedriver::new()
+        }
+        .platform_name()
+    };
+    assert_eq!("linux64", platform_name(Os::Linux, Arch::X64).unwrap());
+    assert_eq!("mac-x64", platform_name(Os::Mac, Arch::X64).unwrap());
+    assert_eq!("mac-arm64", platform_name(Os::Mac, Arch::Arm64).unwrap());
+    assert_eq!("win64", platform_name(Os::Windows, Arch::X64).unwrap());
+    assert_eq!("win32", platform_name(Os::Windows, Arch::X86).unwrap());
+    assert_eq!(
+        "chromedriver is not available for linux-arm64",
+        platform_name(Os::Linux, Arch::Arm64)
+            .unwrap_err()
+            .to_string()
+    );
+}
+
 #[test]
 fn direct_download_url_test() {
-    #[cfg(target_os = "linux")]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
     assert_eq!(
         "https://storage.googleapis.com/chrome-for-testing-public/v1/linux64/chromedriver-linux64.zip",
         Chromedriver::new()
@@ -310,7 +981,7 @@ fn direct_download_url_test() {
             .unwrap()
             .to_string()
     );
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
     assert_eq!(
         "https://storage.googleapis.com/chrome-for-testing-public/v1/mac-x64/chromedriver-mac-x64.zip",
         Chromedriver::new()
@@ -318,9 +989,9 @@ fn direct_download_url_test() {
             .unwrap()
             .to_string()
     );
-    #[cfg(target_os = "windows")]
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
     assert_eq!(
-        "https://storage.googleapis.com/chrome-for-testing-public/v1/win32/chromedriver-win32.zip",
+        "https://storage.googleapis.com/chrome-for-testing-public/v1/win64/chromedriver-win64.zip",
         Chromedriver::new()
             .direct_download_url("v1")
             .unwrap()
@@ -0,0 +1,352 @@
+/// This module manages version selection of the `msedgedriver`,
+/// based on the installed browser version.
+///
+/// See https://learn.microsoft.com/en-us/microsoft-edge/webdriver-chromium/
+use tracing::debug;
+use url::Url;
+
+use crate::chromedriver::Version;
+use crate::http::HttpClient;
+use crate::platform::{Arch, Os, Platform};
+use crate::{mirror_url, DriverFetcher, Error, Result};
+
+use std::path::PathBuf;
+
+pub struct Edgedriver {
+    base_url: String,
+    platform: Option<Platform>,
+    http: HttpClient,
+}
+
+impl DriverFetcher for Edgedriver {
+    const BASE_URL: &'static str = "https://msedgedriver.microsoft.com";
+    const MIRROR_ENV: &'static str = "WEBDRIVER_INSTALL_EDGEDRIVER_MIRROR";
+
+    /// Returns the latest stable version of the driver
+    fn latest_version(&self) -> Result<String> {
+        let version = version_from_file(&self.http.get(&self.latest_stable_url())?.bytes()?)?;
+        debug!("Latest stable version: {}", version);
+        Ok(version)
+    }
+
+    #[cfg(feature = "async")]
+    async fn latest_version_async(&self) -> Result<String> {
+        let resp = self.http.get_async(&self.latest_stable_url()).await?;
+        let version = version_from_file(&self.http.read(resp.bytes()).await?)?;
+        debug!("Latest stable version: {}", version);
+        Ok(version)
+    }
+
+    /// Returns the download url for the driver executable
+    fn direct_download_url(&self, version: &str) -> Result<Url> {
+        Ok(Url::parse(&format!(
+            "{}/{version}/edgedriver_{platform}.zip",
+            self.base_url,
+            version = version,
+            platform = self.platform_name()?
+        ))?)
+    }
+
+    /// Fails, as there is no index of the released versions
+    fn available_versions(&self, _platform_only: bool) -> Result<Vec<String>> {
+        Err(Error::VersionNotFound(String::from(
+            "msedgedriver versions can't be listed",
+        )))
+    }
+
+    #[cfg(feature = "async")]
+    async fn available_versions_async(&self, platform_only: bool) -> Result<Vec<String>> {
+        self.available_versions(platform_only)
+    }
+}
+
+impl Edgedriver {
+    pub fn new() -> Self {
+        Self {
+            base_url: mirror_url(Self::MIRROR_ENV, Self::BASE_URL),
+            platform: None,
+            http: HttpClient::new(),
+        }
+    }
+
+    /// Downloads the driver for `platform` instead of the host platform
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Sends the requests with `client` instead of the default one
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Resolves and downloads drivers from `url` instead of the Microsoft servers
+    pub fn mirror(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Returns the driver version matching the installed Edge browser
+    ///
+    /// Falls back to the latest stable version if no browser could be found.
+    pub fn matching_version(&self) -> Result<String> {
+        match Location::location().and_then(|path| Version::from_executable(&path)) {
+            Ok(browser_version) => self.version_for(&browser_version),
+            Err(e) => {
+                debug!("{}, falling back to the latest msedgedriver", e);
+                self.latest_version()
+            }
+        }
+    }
+
+    /// Returns the latest driver version for the major version of the given browser version
+    pub fn version_for(&self, browser_version: &Version) -> Result<String> {
+        let resp = self.http.get(&self.latest_release_url(browser_version)?)?;
+        let version = version_from_file(&resp.bytes()?)?;
+        debug!("Found msedgedriver {} by major version", version);
+        Ok(version)
+    }
+
+    /// Async variant of [`Edgedriver::matching_version`]
+    #[cfg(feature = "async")]
+    pub async fn matching_version_async(&self) -> Result<String> {
+        match Location::location().and_then(|path| Version::from_executable(&path)) {
+            Ok(browser_version) => self.version_for_async(&browser_version).await,
+            Err(e) => {
+                debug!("{}, falling back to the latest msedgedriver", e);
+                self.latest_version_async().await
+            }
+        }
+    }
+
+    /// Async variant of [`Edgedriver::version_for`]
+    #[cfg(feature = "async")]
+    pub async fn version_for_async(&self, browser_version: &Version) -> Result<String> {
+        let resp = self
+            .http
+            .get_async(&self.latest_release_url(browser_version)?)
+            .await?;
+        let version = version_from_file(&self.http.read(resp.bytes()).await?)?;
+        debug!("Found msedgedriver {} by major version", version);
+        Ok(version)
+    }
+
+    fn latest_stable_url(&self) -> String {
+        format!("{}/LATEST_STABLE", self.base_url)
+    }
+
+    /// Returns the URL of the file with the latest driver version for the browser's major version
+    fn latest_release_url(&self, browser_version: &Version) -> Result<String> {
+        Ok(format!(
+            "{}/LATEST_RELEASE_{}_{}",
+            self.base_url,
+            browser_version.major(),
+            self.os_name()?
+        ))
+    }
+
+    /// Returns the platform part to be used in the download URL
+    fn platform_name(&self) -> Result<&'static str> {
+        let platform = self.platform.map_or_else(Platform::host, Ok)?;
+        match (platform.os, platform.arch) {
+            (Os::Linux, Arch::X64) => Ok("linux64"),
+            (Os::Mac, Arch::X64) => Ok("mac64"),
+            (Os::Mac, Arch::Arm64) => Ok("mac64_m1"),
+            (Os::Windows, Arch::X64) => Ok("win64"),
+            (Os::Windows, Arch::X86) => Ok("win32"),
+            (Os::Windows, Arch::Arm64) => Ok("arm64"),
+            _ => Err(platform.unsupported("msedgedriver")),
+        }
+    }
+
+    /// Returns the OS part to be used in the `LATEST_RELEASE_<major>_<os>` URL
+    fn os_name(&self) -> Result<&'static str> {
+        let platform = self.platform.map_or_else(Platform::host, Ok)?;
+        Ok(match platform.os {
+            Os::Linux => "LINUX",
+            Os::Mac => "MACOS",
+            Os::Windows => "WINDOWS",
+        })
+    }
+}
+
+/// Parses the contents of a `LATEST_*` version file
+///
+/// These files are served as UTF-16 with a byte order mark, but plain
+/// UTF-8 is accepted as well.
+fn version_from_file(bytes: &[u8]) -> Result<String> {
+    let content = match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16(&units).map_err(|e| Error::VersionParse(e.to_string()))?
+        }
+        _ => String::from_utf8(bytes.to_vec()).map_err(|e| Error::VersionParse(e.to_string()))?,
+    };
+    let version = content.trim().trim_start_matches('\u{feff}');
+    if version.is_empty() {
+        return Err(Error::VersionParse(String::from(
+            "Could not find an msedgedriver version",
+        )));
+    }
+    Ok(version.to_string())
+}
+
+struct Location {}
+
+#[cfg(target_os = "linux")]
+static LINUX_EDGE_DIRS: &[&str] = &[
+    "/usr/local/sbin",
+    "/usr/local/bin",
+    "/usr/sbin",
+    "/usr/bin",
+    "/sbin",
+    "/bin",
+    "/opt/microsoft/msedge",
+];
+#[cfg(target_os = "linux")]
+static LINUX_EDGE_FILES: &[&str] = &[
+    "microsoft-edge",
+    "microsoft-edge-stable",
+    "microsoft-edge-beta",
+    "microsoft-edge-dev",
+    "msedge",
+];
+
+#[cfg(target_os = "windows")]
+static WIN_EDGE_DIRS: &[&str] = &["Microsoft\\Edge\\Application"];
+
+#[cfg(target_os = "macos")]
+static MAC_EDGE_FILES: &[&str] = &[
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    "/Applications/Microsoft Edge Beta.app/Contents/MacOS/Microsoft Edge Beta",
+    "/Applications/Microsoft Edge Dev.app/Contents/MacOS/Microsoft Edge Dev",
+];
+
+impl Location {
+    /// Returns the location of the currently installed Edge browser
+    pub fn location() -> Result<PathBuf> {
+        #[cfg(target_os = "linux")]
+        return Self::linux_location();
+        #[cfg(target_os = "windows")]
+        return Self::windows_location();
+        #[cfg(target_os = "macos")]
+        return Self::mac_location();
+    }
+
+    #[cfg(target_os = "linux")]
+    fn linux_location() -> Result<PathBuf> {
+        for dir in LINUX_EDGE_DIRS.iter().map(PathBuf::from) {
+            for file in LINUX_EDGE_FILES {
+                let path = dir.join(file);
+                if path.exists() {
+                    return Ok(path);
+                }
+            }
+        }
+        Err(Error::BrowserNotFound("edge"))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn windows_location() -> Result<PathBuf> {
+        use dirs_sys::known_folder;
+
+        let roots = vec![
+            known_folder(&winapi::um::knownfolders::FOLDERID_ProgramFilesX86),
+            known_folder(&winapi::um::knownfolders::FOLDERID_ProgramFiles),
+            known_folder(&winapi::um::knownfolders::FOLDERID_ProgramFilesX64),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<PathBuf>>();
+        for dir in WIN_EDGE_DIRS.iter().map(PathBuf::from) {
+            for root in &roots {
+                let path = root.join(&dir).join("msedge.exe");
+                debug!("checking path {}", &path.display());
+                if path.exists() {
+                    return Ok(path);
+                }
+            }
+        }
+        Err(Error::BrowserNotFound("edge"))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn mac_location() -> Result<PathBuf> {
+        for path in MAC_EDGE_FILES.iter().map(PathBuf::from) {
+            if path.exists() {
+                return Ok(path);
+            }
+        }
+        Err(Error::BrowserNotFound("edge"))
+    }
+}
+
+#[test]
+fn version_from_file_test() {
+    assert_eq!(
+        "118.0.2088.76",
+        version_from_file(b"118.0.2088.76\r\n").unwrap()
+    );
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in "118.0.2088.76\r\n".encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    assert_eq!("118.0.2088.76", version_from_file(&utf16).unwrap());
+}
+
+#[test]
+#[should_panic(expected = "Could not find an msedgedriver version")]
+fn version_from_file_empty_test() {
+    version_from_file(b"\r\n").unwrap();
+}
+
+#[test]
+fn platform_name_test() {
+    let platform_name = |os, arch| {
+        Edgedriver {
+            platform: Some(Platform::new(os, arch)),
+            ..Edgedriver::new()
+        }
+        .platform_name()
+    };
+    assert_eq!("linux64", platform_name(Os::Linux, Arch::X64).unwrap());
+    assert_eq!("mac64", platform_name(Os::Mac, Arch::X64).unwrap());
+    assert_eq!("mac64_m1", platform_name(Os::Mac, Arch::Arm64).unwrap());
+    assert_eq!("win64", platform_name(Os::Windows, Arch::X64).unwrap());
+    assert_eq!("win32", platform_name(Os::Windows, Arch::X86).unwrap());
+    assert_eq!("arm64", platform_name(Os::Windows, Arch::Arm64).unwrap());
+    assert!(platform_name(Os::Linux, Arch::Arm64).is_err());
+}
+
+#[test]
+fn direct_download_url_test() {
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    assert_eq!(
+        "https://msedgedriver.microsoft.com/v1/edgedriver_linux64.zip",
+        Edgedriver::new()
+            .direct_download_url("v1")
+            .unwrap()
+            .to_string()
+    );
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    assert_eq!(
+        "https://msedgedriver.microsoft.com/v1/edgedriver_mac64.zip",
+        Edgedriver::new()
+            .direct_download_url("v1")
+            .unwrap()
+            .to_string()
+    );
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    assert_eq!(
+        "https://msedgedriver.microsoft.com/v1/edgedriver_win64.zip",
+        Edgedriver::new()
+            .direct_download_url("v1")
+            .unwrap()
+            .to_string()
+    );
+}
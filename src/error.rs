@@ -0,0 +1,60 @@
+//! Errors returned by `webdriver-install`
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The browser to select the driver version for isn't installed
+    #[error("Unable to find {0} executable")]
+    BrowserNotFound(&'static str),
+    /// The driver isn't released for the platform, or the host platform isn't supported
+    #[error("{driver} is not available for {platform}")]
+    UnsupportedPlatform { driver: String, platform: String },
+    /// A platform given as a string couldn't be parsed
+    #[error("unknown platform '{0}', expected e.g. 'linux-x64'")]
+    InvalidPlatform(String),
+    /// A Chrome for Testing channel given as a string couldn't be parsed
+    #[error("unknown channel '{0}', expected one of stable, beta, dev or canary")]
+    InvalidChannel(String),
+    /// A request to the download servers failed
+    #[error("Download failed")]
+    Download(#[from] reqwest::Error),
+    /// The servers don't list a driver version matching the request
+    #[error("{0}")]
+    VersionNotFound(String),
+    /// A browser or driver version couldn't be parsed
+    #[error("{0}")]
+    VersionParse(String),
+    /// A driver archive couldn't be read, or doesn't contain the driver
+    #[error("{0}")]
+    Archive(String),
+    /// The checksum file doesn't list the archive
+    #[error("No checksum for {archive} in {checksum_file:?}")]
+    ChecksumNotFound {
+        archive: String,
+        checksum_file: PathBuf,
+    },
+    /// The SHA-256 digest of the archive doesn't match the expected one
+    #[error("SHA-256 checksum mismatch for {archive}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        archive: String,
+        expected: String,
+        actual: String,
+    },
+    /// The installation directory doesn't exist or isn't a directory
+    #[error("installation directory {0:?} must be an existing directory")]
+    InvalidDirectory(PathBuf),
+    /// The home or cache directory of the user couldn't be determined
+    #[error("Unable to find the {0} directory")]
+    DirectoryNotFound(&'static str),
+    /// The proxy or certificates given to [`HttpClient`](crate::http::HttpClient) are invalid
+    #[error("invalid HTTP client configuration: {0}")]
+    HttpConfig(String),
+    #[error("invalid download URL")]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -1,63 +1,418 @@
-use crate::DriverFetcher;
-use eyre::{eyre, Result};
+use crate::http::HttpClient;
+use crate::platform::{Arch, Os, Platform};
+use crate::{mirror_url, DriverFetcher, Error, Result};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use serde_json::Value;
+use tracing::debug;
 use url::Url;
 
-pub struct Geckodriver;
+/// The GitHub releases API of the geckodriver repository
+///
+/// See https://docs.github.com/en/rest/releases/releases
+const API_URL: &str = "https://api.github.com/repos/mozilla/geckodriver";
+const API_MIRROR_ENV: &str = "WEBDRIVER_INSTALL_GECKODRIVER_API_MIRROR";
+const RELEASES_PER_PAGE: usize = 100;
+
+pub struct Geckodriver {
+    base_url: String,
+    api_url: Option<String>,
+    token: Option<String>,
+    platform: Option<Platform>,
+    http: HttpClient,
+}
 
 impl DriverFetcher for Geckodriver {
     const BASE_URL: &'static str = "https://github.com/mozilla/geckodriver/releases";
+    const MIRROR_ENV: &'static str = "WEBDRIVER_INSTALL_GECKODRIVER_MIRROR";
 
     /// Returns the latest version of the driver
+    ///
+    /// This is looked up with the GitHub API if a token is configured, and from
+    /// the redirect of the latest release page otherwise.
     fn latest_version(&self) -> Result<String> {
-        let latest_release_url = format!("{}/latest", Self::BASE_URL);
-        let resp = reqwest::blocking::get(&latest_release_url)?;
-        let url = resp.url();
-        Ok(url.path_segments().unwrap().last().unwrap().to_string())
+        if let Some(url) = self.api_release_url("latest") {
+            let release = self
+                .http
+                .get_with_headers(&url, self.api_headers()?)?
+                .json()?;
+            return tag_name(&release);
+        }
+        let resp = self.http.get(&self.latest_release_url())?;
+        Ok(release_tag_from_url(resp.url()))
+    }
+
+    #[cfg(feature = "async")]
+    async fn latest_version_async(&self) -> Result<String> {
+        if let Some(url) = self.api_release_url("latest") {
+            let resp = self
+                .http
+                .get_with_headers_async(&url, self.api_headers()?)
+                .await?;
+            return tag_name(&self.http.read(resp.json::<Value>()).await?);
+        }
+        let resp = self.http.get_async(&self.latest_release_url()).await?;
+        Ok(release_tag_from_url(resp.url()))
     }
 
     /// Returns the download url for the driver executable
+    ///
+    /// Versions without the leading `v` of the release tag are accepted as well.
     fn direct_download_url(&self, version: &str) -> Result<Url> {
+        let version = Self::release_tag(version);
         Ok(Url::parse(&format!(
             "{}/download/{version}/geckodriver-{version}-{platform}",
-            Self::BASE_URL,
+            self.base_url,
             version = version,
-            platform = Self::platform()?
+            platform = self.platform_name()?
         ))?)
     }
+
+    /// Returns the releases listed by the GitHub API
+    ///
+    /// A token isn't required for this, but raises the rate limit.
+    fn available_versions(&self, platform_only: bool) -> Result<Vec<String>> {
+        let mut versions = vec![];
+        for page in 1.. {
+            let releases: Value = self
+                .http
+                .get_with_headers(&self.releases_page_url(page)?, self.api_headers()?)?
+                .json()?;
+            if !self.push_versions(&releases, platform_only, &mut versions)? {
+                break;
+            }
+        }
+        // The API lists the newest releases first
+        versions.reverse();
+        Ok(versions)
+    }
+
+    #[cfg(feature = "async")]
+    async fn available_versions_async(&self, platform_only: bool) -> Result<Vec<String>> {
+        let mut versions = vec![];
+        for page in 1.. {
+            let resp = self
+                .http
+                .get_with_headers_async(&self.releases_page_url(page)?, self.api_headers()?)
+                .await?;
+            let releases = self.http.read(resp.json::<Value>()).await?;
+            if !self.push_versions(&releases, platform_only, &mut versions)? {
+                break;
+            }
+        }
+        versions.reverse();
+        Ok(versions)
+    }
 }
 
 impl Geckodriver {
     pub fn new() -> Self {
-        Self {}
-    }
-
-    fn platform() -> Result<String> {
-        match std::env::consts::OS {
-            "linux" => Ok(format!("linux{}.tar.gz", Self::pointer_width())),
-            "macos" => Ok(String::from("macos.tar.gz")),
-            "windows" => Ok(format!("win{}.zip", Self::pointer_width())),
-            other => Err(eyre!(
-                "webdriver-install doesn't support '{}' currently",
-                other
-            )),
+        let base_url = mirror_url(Self::MIRROR_ENV, Self::BASE_URL);
+        let api_url = mirror_url(API_MIRROR_ENV, API_URL);
+        Self {
+            // Releases from a mirror aren't looked up on GitHub, unless the API is mirrored too
+            api_url: (base_url == Self::BASE_URL || api_url != API_URL).then_some(api_url),
+            base_url,
+            token: std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty()),
+            platform: None,
+            http: HttpClient::new(),
+        }
+    }
+
+    /// Downloads the driver for `platform` instead of the host platform
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Sends the requests with `client` instead of the default one
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Resolves and downloads releases from `url` instead of GitHub
+    ///
+    /// The GitHub API isn't used for the mirror, unless it's set with [`Geckodriver::api_mirror`].
+    pub fn mirror(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self.api_url = None;
+        self
+    }
+
+    /// Sends the GitHub releases API requests to `url` instead of `api.github.com`
+    pub fn api_mirror(mut self, url: &str) -> Self {
+        self.api_url = Some(url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Authenticates the GitHub API requests with `token` instead of `GITHUB_TOKEN`
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Returns the download url for the driver executable
+    ///
+    /// With a GitHub token this is the URL of the release asset listed by the
+    /// GitHub API, see [`DriverFetcher::direct_download_url`] otherwise.
+    pub fn download_url(&self, version: &str) -> Result<Url> {
+        let tag = Self::release_tag(version);
+        match self.api_release_url(&format!("tags/{}", tag)) {
+            Some(url) => {
+                let release = self
+                    .http
+                    .get_with_headers(&url, self.api_headers()?)?
+                    .json()?;
+                self.asset_url(&release, &tag)
+            }
+            None => self.direct_download_url(version),
+        }
+    }
+
+    /// Async variant of [`Geckodriver::download_url`]
+    #[cfg(feature = "async")]
+    pub async fn download_url_async(&self, version: &str) -> Result<Url> {
+        let tag = Self::release_tag(version);
+        match self.api_release_url(&format!("tags/{}", tag)) {
+            Some(url) => {
+                let resp = self
+                    .http
+                    .get_with_headers_async(&url, self.api_headers()?)
+                    .await?;
+                self.asset_url(&self.http.read(resp.json::<Value>()).await?, &tag)
+            }
+            None => self.direct_download_url(version),
+        }
+    }
+
+    /// Returns the GitHub API URL of the given page of releases
+    fn releases_page_url(&self, page: usize) -> Result<String> {
+        let api_url = self.api_url.as_ref().ok_or_else(|| {
+            Error::VersionNotFound(String::from(
+                "geckodriver versions can only be listed with the GitHub API",
+            ))
+        })?;
+        Ok(format!(
+            "{}/releases?per_page={}&page={}",
+            api_url, RELEASES_PER_PAGE, page
+        ))
+    }
+
+    /// Adds the versions of a page of `releases` to `versions`
+    ///
+    /// Returns whether there might be another page.
+    fn push_versions(
+        &self,
+        releases: &Value,
+        platform_only: bool,
+        versions: &mut Vec<String>,
+    ) -> Result<bool> {
+        let releases = releases.as_array().map(Vec::as_slice).unwrap_or_default();
+        for release in releases {
+            let tag = tag_name(release)?;
+            if !platform_only || find_asset(release, &self.asset_name(&tag)?).is_some() {
+                versions.push(tag);
+            }
+        }
+        Ok(releases.len() == RELEASES_PER_PAGE)
+    }
+
+    /// Returns the name of the release asset for the platform
+    fn asset_name(&self, tag: &str) -> Result<String> {
+        Ok(format!("geckodriver-{}-{}", tag, self.platform_name()?))
+    }
+
+    /// Returns the GitHub API URL of the given release, if a token is configured
+    fn api_release_url(&self, release: &str) -> Option<String> {
+        match (&self.api_url, &self.token) {
+            (Some(api_url), Some(_)) => Some(format!("{}/releases/{}", api_url, release)),
+            _ => None,
         }
     }
 
-    const fn pointer_width() -> usize {
-        #[cfg(target_pointer_width = "32")]
-        {
-            32
+    fn api_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.github+json"),
+        );
+        if let Some(token) = &self.token {
+            let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|_| Error::HttpConfig(String::from("invalid GitHub token")))?;
+            authorization.set_sensitive(true);
+            headers.insert(AUTHORIZATION, authorization);
+        }
+        Ok(headers)
+    }
+
+    /// Returns the download URL of the asset for the platform from a GitHub API `release`
+    fn asset_url(&self, release: &Value, tag: &str) -> Result<Url> {
+        let name = self.asset_name(tag)?;
+        let url = find_asset(release, &name).ok_or_else(|| {
+            Error::VersionNotFound(format!("No {} in the geckodriver {} release", name, tag))
+        })?;
+        debug!("Found geckodriver asset {}", url);
+        Ok(Url::parse(url)?)
+    }
+
+    /// Returns the URL redirecting to the latest release
+    fn latest_release_url(&self) -> String {
+        format!("{}/latest", self.base_url)
+    }
+
+    /// Returns the release tag of the given version, e.g. `v0.33.0` for `0.33.0`
+    pub(crate) fn release_tag(version: &str) -> String {
+        if version.starts_with('v') {
+            version.to_string()
+        } else {
+            format!("v{}", version)
         }
-        #[cfg(target_pointer_width = "64")]
-        {
-            64
+    }
+
+    /// Returns the platform part to be used in the download URL
+    ///
+    /// See https://github.com/mozilla/geckodriver/releases for the available assets.
+    fn platform_name(&self) -> Result<&'static str> {
+        let platform = self.platform.map_or_else(Platform::host, Ok)?;
+        match (platform.os, platform.arch) {
+            (Os::Linux, Arch::X64) => Ok("linux64.tar.gz"),
+            (Os::Linux, Arch::X86) => Ok("linux32.tar.gz"),
+            (Os::Linux, Arch::Arm64) => Ok("linux-aarch64.tar.gz"),
+            (Os::Mac, Arch::X64) => Ok("macos.tar.gz"),
+            (Os::Mac, Arch::Arm64) => Ok("macos-aarch64.tar.gz"),
+            (Os::Windows, Arch::X64) => Ok("win64.zip"),
+            (Os::Windows, Arch::X86) => Ok("win32.zip"),
+            (Os::Windows, Arch::Arm64) => Ok("win-aarch64.zip"),
+            _ => Err(platform.unsupported("geckodriver")),
         }
     }
 }
 
+/// Returns the tag of a GitHub API `release`
+fn tag_name(release: &Value) -> Result<String> {
+    let tag = release["tag_name"].as_str().ok_or_else(|| {
+        Error::VersionParse(String::from("Could not find the geckodriver release tag"))
+    })?;
+    Ok(tag.to_string())
+}
+
+/// Returns the download URL of the asset called `name` in a GitHub API `release`
+fn find_asset<'a>(release: &'a Value, name: &str) -> Option<&'a str> {
+    release["assets"]
+        .as_array()?
+        .iter()
+        .find(|asset| asset["name"] == name)
+        .and_then(|asset| asset["browser_download_url"].as_str())
+}
+
+/// Returns the last path segment of the release URL, e.g. `v0.33.0` for `.../tag/v0.33.0`
+fn release_tag_from_url(url: &Url) -> String {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[test]
+fn platform_name_test() {
+    let platform_name = |os, arch| {
+        Geckodriver {
+            platform: Some(Platform::new(os, arch)),
+            ..Geckodriver::new()
+        }
+        .platform_name()
+    };
+    assert_eq!(
+        "linux64.tar.gz",
+        platform_name(Os::Linux, Arch::X64).unwrap()
+    );
+    assert_eq!(
+        "linux32.tar.gz",
+        platform_name(Os::Linux, Arch::X86).unwrap()
+    );
+    assert_eq!(
+        "linux-aarch64.tar.gz",
+        platform_name(Os::Linux, Arch::Arm64).unwrap()
+    );
+    assert_eq!("macos.tar.gz", platform_name(Os::Mac, Arch::X64).unwrap());
+    assert_eq!(
+        "macos-aarch64.tar.gz",
+        platform_name(Os::Mac, Arch::Arm64).unwrap()
+    );
+    assert_eq!("win64.zip", platform_name(Os::Windows, Arch::X64).unwrap());
+    assert_eq!("win32.zip", platform_name(Os::Windows, Arch::X86).unwrap());
+    assert_eq!(
+        "win-aarch64.zip",
+        platform_name(Os::Windows, Arch::Arm64).unwrap()
+    );
+    assert!(platform_name(Os::Mac, Arch::X86).is_err());
+}
+
+#[test]
+fn asset_url_test() {
+    let release = serde_json::json!({
+        "tag_name": "v0.34.0",
+        "assets": [
+            {
+                "name": "geckodriver-v0.34.0-linux64.tar.gz",
+                "browser_download_url": "https://example.com/linux64.tar.gz"
+            },
+            {
+                "name": "geckodriver-v0.34.0-win64.zip",
+                "browser_download_url": "https://example.com/win64.zip"
+            }
+        ]
+    });
+    let geckodriver = |os, arch| Geckodriver::new().platform(Platform::new(os, arch));
+
+    assert_eq!("v0.34.0", tag_name(&release).unwrap());
+    assert_eq!(
+        "https://example.com/win64.zip",
+        geckodriver(Os::Windows, Arch::X64)
+            .asset_url(&release, "v0.34.0")
+            .unwrap()
+            .as_str()
+    );
+    assert!(matches!(
+        geckodriver(Os::Mac, Arch::Arm64).asset_url(&release, "v0.34.0"),
+        Err(Error::VersionNotFound(_))
+    ));
+}
+
+#[test]
+fn api_release_url_test() {
+    let geckodriver = Geckodriver {
+        token: None,
+        ..Geckodriver::new()
+    };
+    assert_eq!(None, geckodriver.api_release_url("latest"));
+    assert_eq!(
+        Some("https://api.github.com/repos/mozilla/geckodriver/releases/latest"),
+        geckodriver
+            .token("secret")
+            .api_release_url("latest")
+            .as_deref()
+    );
+    assert_eq!(
+        None,
+        Geckodriver::new()
+            .token("secret")
+            .mirror("https://mirror.example.com")
+            .api_release_url("latest")
+    );
+}
+
+#[test]
+fn direct_download_url_without_v_test() {
+    assert_eq!(
+        Geckodriver::new().direct_download_url("v0.33.0").unwrap(),
+        Geckodriver::new().direct_download_url("0.33.0").unwrap()
+    );
+}
+
 #[test]
 fn direct_download_url_test() {
-    #[cfg(target_os = "linux")]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
     assert_eq!(
         "https://github.com/mozilla/geckodriver/releases/download/v1/geckodriver-v1-linux64.tar.gz",
         Geckodriver::new()
@@ -65,7 +420,7 @@ fn direct_download_url_test() {
             .unwrap()
             .to_string()
     );
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
     assert_eq!(
         "https://github.com/mozilla/geckodriver/releases/download/v1/geckodriver-v1-macos.tar.gz",
         Geckodriver::new()
@@ -73,7 +428,7 @@ fn direct_download_url_test() {
             .unwrap()
             .to_string()
     );
-    #[cfg(target_os = "windows")]
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
     assert_eq!(
         "https://github.com/mozilla/geckodriver/releases/download/v1/geckodriver-v1-win64.zip",
         Geckodriver::new()
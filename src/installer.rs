@@ -1,33 +1,57 @@
-use crate::{chromedriver::Chromedriver, geckodriver::Geckodriver, DriverFetcher};
+use crate::{
+    cache::{version_key, Cache},
+    chromedriver::{Channel, Chromedriver},
+    edgedriver::Edgedriver,
+    geckodriver::Geckodriver,
+    http::HttpClient,
+    platform::{Os, Platform},
+    DriverFetcher, Error, Result,
+};
 use dirs::home_dir;
-use eyre::{ensure, eyre, Result};
 use flate2::read::GzDecoder;
+use fs4::{FileExt, TryLockError};
+use sha2::{Digest, Sha256};
 use tar::Archive;
+use tempfile::NamedTempFile;
 use tracing::debug;
+use url::Url;
 
-use std::fs::File;
-use std::io::{Cursor, Read};
-use std::path::PathBuf;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-static DRIVER_EXECUTABLES: &[&'static str] = &[
+static DRIVER_EXECUTABLES: &[&str] = &[
     "geckodriver",
     "chromedriver",
+    "msedgedriver",
     "chromedriver.exe",
     "geckodriver.exe",
+    "msedgedriver.exe",
 ];
 
+/// How often [`lock_async`] retries to acquire a lock held by another installation
+#[cfg(feature = "async")]
+const LOCK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Driver {
     Chrome,
     Gecko,
+    Edge,
 }
 
 impl Driver {
+    /// All supported drivers
+    pub const ALL: &'static [Driver] = &[Self::Chrome, Self::Gecko, Self::Edge];
+
     /// Downloads and unarchives the driver executable to $HOME/.webdrivers
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # fn main() -> eyre::Result<()> {
+    /// # fn main() -> webdriver_install::Result<()> {
     /// use webdriver_install::Driver;
     ///
     /// // Install geckodriver
@@ -39,17 +63,18 @@ impl Driver {
     /// # }
     /// ```
     pub fn install(&self) -> Result<PathBuf> {
-        let target_dir = home_dir().unwrap().join(".webdrivers");
-        std::fs::create_dir_all(&target_dir)?;
-        self.install_into(target_dir)
+        self.install_into(default_dir()?)
     }
 
     /// Downloads and unarchives the driver executable into the specified `target_dir`
     ///
+    /// For `chromedriver` and `msedgedriver`, the driver version matching the installed
+    /// Chrome/Edge browser is selected. If no browser is found, the latest version is installed.
+    ///
     /// # Example
     ///
     /// ```no_run
-    /// # fn main() -> eyre::Result<()> {
+    /// # fn main() -> webdriver_install::Result<()> {
     /// use webdriver_install::Driver;
     /// use std::path::PathBuf;
     ///
@@ -62,45 +87,180 @@ impl Driver {
     /// # }
     /// ```
     pub fn install_into(&self, target_dir: PathBuf) -> Result<PathBuf> {
-        ensure!(target_dir.exists(), "installation directory must exist.");
-        ensure!(
-            target_dir.is_dir(),
-            "installation location must be a directory."
-        );
+        Installer::new(*self).install_into(target_dir)
+    }
+
+    /// Async variant of [`Driver::install_into`], for use inside an async runtime
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn install() -> webdriver_install::Result<()> {
+    /// use webdriver_install::Driver;
+    /// use std::path::PathBuf;
+    ///
+    /// Driver::Chrome
+    ///     .install_into_async(PathBuf::from("/tmp/webdrivers"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn install_into_async(&self, target_dir: PathBuf) -> Result<PathBuf> {
+        Installer::new(*self).install_into_async(target_dir).await
+    }
+
+    /// Downloads and unarchives the given `version` of the driver executable into the
+    /// specified `target_dir`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::Driver;
+    /// use std::path::PathBuf;
+    ///
+    /// // Install geckodriver 0.33.0 into /tmp/webdrivers
+    /// Driver::Gecko.install_version("v0.33.0", PathBuf::from("/tmp/webdrivers"))?;
+    ///
+    /// // Install chromedriver 118.0.5993.70 into /tmp/webdrivers
+    /// Driver::Chrome.install_version("118.0.5993.70", PathBuf::from("/tmp/webdrivers"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn install_version(&self, version: &str, target_dir: PathBuf) -> Result<PathBuf> {
+        Installer::new(*self)
+            .version(version)
+            .install_into(target_dir)
+    }
+
+    /// Updates the driver in $HOME/.webdrivers, if it is outdated
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::{installer::Update, Driver};
+    ///
+    /// if let Update::Updated { from, to } = Driver::Chrome.update()? {
+    ///     println!("Updated chromedriver from {:?} to {}", from, to);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update(&self) -> Result<Update> {
+        self.update_in(default_dir()?)
+    }
+
+    /// Updates the driver installed in `dir`, if it is outdated
+    ///
+    /// The installed version is compared with the version [`Driver::install_into`]
+    /// would install, and only replaced if that one is newer. Nothing is installed if
+    /// the driver isn't present in `dir`.
+    pub fn update_in(&self, dir: PathBuf) -> Result<Update> {
+        Installer::new(*self).update_in(dir)
+    }
+
+    /// Removes the driver from $HOME/.webdrivers
+    pub fn uninstall(&self) -> Result<Vec<PathBuf>> {
+        self.uninstall_from(default_dir()?)
+    }
+
+    /// Removes the driver executable, its version metadata and its lock file from `dir`
+    ///
+    /// Only files `webdriver-install` itself would create are removed, everything else
+    /// in `dir` is left alone. Waits for installations in progress to finish first.
+    /// The lock file is only removed on unix. Returns the paths of the removed files.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::Driver;
+    /// use std::path::PathBuf;
+    ///
+    /// // Remove geckodriver from /tmp/webdrivers
+    /// Driver::Gecko.uninstall_from(PathBuf::from("/tmp/webdrivers"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn uninstall_from(&self, dir: PathBuf) -> Result<Vec<PathBuf>> {
+        let mut removed = vec![];
+        for executable in self.executable_names() {
+            let lock_path = dir.join(lock_file_name(executable));
+            let paths = [
+                dir.join(executable),
+                dir.join(metadata_file_name(executable)),
+            ];
+            let locked_before = lock_path.is_file();
+            if !locked_before && !paths.iter().any(|path| path.is_file()) {
+                continue;
+            }
 
-        let download_url = match self {
-            Self::Gecko => {
-                let version = Geckodriver::new().latest_version()?;
-                Geckodriver::new().direct_download_url(&version)?
+            let _lock = lock(&lock_path)?;
+            for path in paths {
+                if path.is_file() {
+                    fs::remove_file(&path)?;
+                    debug!("removed {:?}", path);
+                    removed.push(path);
+                }
             }
-            Self::Chrome => {
-                let version = Chromedriver::new().latest_version()?;
-                Chromedriver::new().direct_download_url(&version)?
+            // Removed while still locked, installations waiting for it lock a new one.
+            // They can't tell a new lock file from the removed one on other platforms,
+            // so it's kept there.
+            if cfg!(unix) {
+                fs::remove_file(&lock_path)?;
+                if locked_before {
+                    removed.push(lock_path);
+                }
             }
-        };
-        let resp = reqwest::blocking::get(download_url.clone())?;
-        let archive_content = &resp.bytes()?;
+        }
+        Ok(removed)
+    }
 
-        let archive_filename = download_url
-            .path_segments()
-            .and_then(|s| s.last())
-            .and_then(|name| if name.is_empty() { None } else { Some(name) })
-            .unwrap_or("tmp.bin");
+    /// Returns the version of the driver installed in `dir`
+    ///
+    /// Returns `None` if the driver is not installed, or if it wasn't installed by
+    /// `webdriver-install` and its version is therefore unknown.
+    pub fn installed_version(&self, dir: &Path) -> Result<Option<String>> {
+        installed_version(dir, &self.executable_name())
+    }
 
-        let executable_path = decompress(archive_filename, archive_content, target_dir.clone())?;
+    /// Returns the file name of the driver executable
+    pub fn executable_name(&self) -> String {
+        format!("{}{}", self.executable_stem(), std::env::consts::EXE_SUFFIX)
+    }
 
-        // Make sure the extracted file will be executable.
-        //
-        // Windows doesn't need that, because all `.exe` files are automatically executable.
-        #[cfg(any(target_os = "linux", target_os = "macos"))]
-        {
-            use std::fs;
-            use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&executable_path, fs::Permissions::from_mode(0o775)).unwrap();
+    /// Returns the file name of the driver executable on `os`
+    fn executable_name_for(&self, os: Os) -> String {
+        match os {
+            Os::Windows => format!("{}.exe", self.executable_stem()),
+            Os::Linux | Os::Mac => self.executable_stem().to_string(),
+        }
+    }
+
+    /// Returns the driver executable names of all platforms
+    fn executable_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        DRIVER_EXECUTABLES
+            .iter()
+            .copied()
+            .filter(move |name| name.trim_end_matches(".exe") == self.executable_stem())
+    }
+
+    fn executable_stem(&self) -> &'static str {
+        match self {
+            Self::Chrome => "chromedriver",
+            Self::Gecko => "geckodriver",
+            Self::Edge => "msedgedriver",
         }
+    }
 
-        debug!("stored at {:?}", executable_path);
-        Ok(executable_path)
+    /// Brings a user provided version into the format used by the driver releases
+    fn normalize_version(&self, version: &str) -> String {
+        match self {
+            Self::Gecko => Geckodriver::release_tag(version),
+            Self::Chrome | Self::Edge => version.to_string(),
+        }
     }
 
     #[doc(hidden)]
@@ -108,68 +268,1064 @@ impl Driver {
         match self {
             Self::Chrome => "chromedriver",
             Self::Gecko => "geckodriver",
+            Self::Edge => "edgedriver",
         }
     }
 
     #[doc(hidden)]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
-        match s {
+        match s.to_ascii_lowercase().as_str() {
             "chromedriver" => Some(Self::Chrome),
             "geckodriver" => Some(Self::Gecko),
+            "edgedriver" => Some(Self::Edge),
             _ => None,
         }
     }
 }
 
-fn decompress(archive_filename: &str, bytes: &[u8], target_dir: PathBuf) -> Result<PathBuf> {
+/// The outcome of [`Driver::update`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    /// The driver is not installed, so it wasn't updated either
+    NotInstalled,
+    /// The installed version is the current one or newer
+    UpToDate(String),
+    /// The installed driver was replaced
+    ///
+    /// `from` is `None` if the previously installed version is unknown.
+    Updated { from: Option<String>, to: String },
+}
+
+/// Installs a driver with non-default options
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> webdriver_install::Result<()> {
+/// use webdriver_install::{Driver, Installer};
+/// use std::path::PathBuf;
+///
+/// // Reinstall chromedriver 118.0.5993.70 into /tmp/webdrivers,
+/// // even if it is installed already
+/// Installer::new(Driver::Chrome)
+///     .version("118.0.5993.70")
+///     .force(true)
+///     .install_into(PathBuf::from("/tmp/webdrivers"))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Installer {
+    driver: Driver,
+    version: Option<String>,
+    force: bool,
+    archive: Option<PathBuf>,
+    mirror: Option<String>,
+    versions_mirror: Option<String>,
+    legacy_mirror: Option<String>,
+    channel: Option<Channel>,
+    sha256: Option<String>,
+    checksum_file: Option<PathBuf>,
+    platform: Option<Platform>,
+    cache: bool,
+    cache_dir: Option<PathBuf>,
+    progress: Option<ProgressHook>,
+    http: HttpClient,
+    github_token: Option<Secret>,
+}
+
+/// Called with the number of bytes downloaded so far and the total size, if known
+type ProgressFn = dyn Fn(u64, Option<u64>) + Send + Sync;
+
+#[derive(Clone)]
+struct ProgressHook(Arc<ProgressFn>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// A token that is left out of the debug output
+#[derive(Clone)]
+struct Secret(String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret")
+    }
+}
+
+impl Installer {
+    pub fn new(driver: Driver) -> Self {
+        Self {
+            driver,
+            version: None,
+            force: false,
+            archive: None,
+            mirror: None,
+            versions_mirror: None,
+            legacy_mirror: None,
+            channel: None,
+            sha256: None,
+            checksum_file: None,
+            platform: None,
+            cache: true,
+            cache_dir: None,
+            progress: None,
+            http: HttpClient::new(),
+            github_token: None,
+        }
+    }
+
+    /// Installs the given driver `version` instead of the default one
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Downloads the driver even if the requested version is installed already
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Installs the driver from a local `.zip` or `.tar.gz` archive instead of downloading it
+    ///
+    /// As the version of the archived driver is unknown, it should be provided with
+    /// [`Installer::version`] to allow skipping the installation next time.
+    pub fn from_archive(mut self, archive: impl Into<PathBuf>) -> Self {
+        self.archive = Some(archive.into());
+        self
+    }
+
+    /// Resolves versions and downloads the driver from `url` instead of the official servers
+    ///
+    /// The mirror has to have the same layout as the server it replaces, see the
+    /// [crate documentation](crate#mirrors).
+    pub fn mirror(mut self, url: impl Into<String>) -> Self {
+        self.mirror = Some(url.into());
+        self
+    }
+
+    /// Fetches the version information from `url`
+    ///
+    /// This replaces the Chrome for Testing JSON API endpoints for `chromedriver`, and
+    /// the GitHub releases API for `geckodriver`, which are served from different
+    /// servers than the drivers.
+    pub fn versions_mirror(mut self, url: impl Into<String>) -> Self {
+        self.versions_mirror = Some(url.into());
+        self
+    }
+
+    /// Downloads `chromedriver` for Chrome before version 115 from `url`
+    ///
+    /// These drivers predate Chrome for Testing, and are resolved and downloaded
+    /// from `https://chromedriver.storage.googleapis.com` by default.
+    pub fn legacy_mirror(mut self, url: impl Into<String>) -> Self {
+        self.legacy_mirror = Some(url.into());
+        self
+    }
+
+    /// Installs the latest `chromedriver` of the Chrome for Testing `channel`
+    ///
+    /// By default, the driver matching the installed Chrome is installed, or the
+    /// latest stable one if there is none. This is only used for `chromedriver`.
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Verifies the archive against the expected SHA-256 `digest` before extracting it
+    ///
+    /// A mismatch fails the installation with an [`Error::ChecksumMismatch`].
+    pub fn sha256(mut self, digest: impl Into<String>) -> Self {
+        self.sha256 = Some(digest.into());
+        self
+    }
+
+    /// Verifies the archive against the SHA-256 digest listed for it in the file at `path`
+    ///
+    /// The file uses the `sha256sum` format, one `<digest>  <archive file name>` per line.
+    /// The installation fails if the archive isn't listed.
+    pub fn checksum_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checksum_file = Some(path.into());
+        self
+    }
+
+    /// Installs the driver for `platform` instead of the host platform
+    ///
+    /// If no version is requested explicitly, the latest driver version is installed
+    /// for platforms other than the host, as the installed browser can't be detected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::platform::{Arch, Os, Platform};
+    /// use webdriver_install::{Driver, Installer};
+    /// use std::path::PathBuf;
+    ///
+    /// // Install the Windows chromedriver.exe into /tmp/webdrivers
+    /// Installer::new(Driver::Chrome)
+    ///     .platform(Platform::new(Os::Windows, Arch::X64))
+    ///     .install_into(PathBuf::from("/tmp/webdrivers"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Reuses downloaded archives across installation directories (enabled by default)
+    ///
+    /// See [`Cache::new`] for the default location of the cache.
+    pub fn cache(mut self, enabled: bool) -> Self {
+        self.cache = enabled;
+        self
+    }
+
+    /// Caches downloaded archives in `dir` instead of the default cache directory
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Sends the requests with `client`, to change timeouts and retries
+    ///
+    /// See [`HttpClient`] for the defaults.
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Looks up `geckodriver` releases with the GitHub API, authenticated with `token`
+    ///
+    /// Defaults to the `GITHUB_TOKEN` environment variable. Without a token, the
+    /// latest release is found by following the redirect of its release page, which
+    /// is subject to stricter rate limits.
+    pub fn github_token(mut self, token: impl Into<String>) -> Self {
+        self.github_token = Some(Secret(token.into()));
+        self
+    }
+
+    /// Calls `progress` while downloading the driver archive
+    ///
+    /// It receives the number of bytes downloaded so far and the total size of the
+    /// archive, if the server sent a `Content-Length`. Archives from the cache or
+    /// from [`Installer::from_archive`] aren't reported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::{Driver, Installer};
+    ///
+    /// Installer::new(Driver::Chrome)
+    ///     .on_progress(|downloaded, total| match total {
+    ///         Some(total) => eprintln!("{}/{} bytes", downloaded, total),
+    ///         None => eprintln!("{} bytes", downloaded),
+    ///     })
+    ///     .install()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_progress(
+        mut self,
+        progress: impl Fn(u64, Option<u64>) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressHook(Arc::new(progress)));
+        self
+    }
+
+    /// Installs the driver into $HOME/.webdrivers
+    pub fn install(&self) -> Result<PathBuf> {
+        self.install_into(default_dir()?)
+    }
+
+    /// Installs the driver into the specified `target_dir`
+    ///
+    /// Nothing is downloaded if the requested version is installed already,
+    /// unless [`Installer::force`] is set.
+    ///
+    /// Concurrent installations of the same driver into `target_dir` are serialized
+    /// with an advisory file lock, also across processes. Waiting installations find
+    /// the driver installed then, and don't download it again.
+    pub fn install_into(&self, target_dir: PathBuf) -> Result<PathBuf> {
+        if !target_dir.is_dir() {
+            return Err(Error::InvalidDirectory(target_dir));
+        }
+
+        if let Some(archive_path) = &self.archive {
+            let _lock = self.lock(&target_dir)?;
+            return self.install_archive(archive_path, target_dir);
+        }
+
+        let (version, download_url) = match self.requested_version() {
+            Some(version) => (version, None),
+            None => self.default_release()?,
+        };
+        self.install_release(target_dir, version, download_url)
+    }
+
+    /// Installs `version` into `target_dir`, from `download_url` if it is known already
+    fn install_release(
+        &self,
+        target_dir: PathBuf,
+        version: String,
+        download_url: Option<Url>,
+    ) -> Result<PathBuf> {
+        let _lock = self.lock(&target_dir)?;
+        if let Some(executable_path) = self.installed_path(&target_dir, &version)? {
+            return Ok(executable_path);
+        }
+
+        let (archive_filename, mut archive) = match self.cached(&version)? {
+            Some(cached) => cached,
+            None => {
+                let download_url = match download_url {
+                    Some(url) => url,
+                    None => self.download_url(&version)?,
+                };
+                let archive_filename = archive_filename(&download_url).to_string();
+                let archive = self.download(&download_url, &archive_filename, &version)?;
+                (archive_filename, archive)
+            }
+        };
+
+        let executable_path = self.unpack(
+            &archive_filename,
+            archive.file(),
+            &target_dir,
+            Some(&version),
+        )?;
+        archive.cache()?;
+        Ok(executable_path)
+    }
+
+    /// Async variant of [`Installer::install_into`], for use inside an async runtime
+    ///
+    /// Only the network requests and waiting for the installation lock are asynchronous,
+    /// the archive is unpacked on the calling thread.
+    #[cfg(feature = "async")]
+    pub async fn install_into_async(&self, target_dir: PathBuf) -> Result<PathBuf> {
+        if !target_dir.is_dir() {
+            return Err(Error::InvalidDirectory(target_dir));
+        }
+
+        if let Some(archive_path) = &self.archive {
+            let _lock = self.lock_async(&target_dir).await?;
+            return self.install_archive(archive_path, target_dir);
+        }
+
+        let (version, download_url) = match self.requested_version() {
+            Some(version) => (version, None),
+            None => self.default_release_async().await?,
+        };
+        let _lock = self.lock_async(&target_dir).await?;
+        if let Some(executable_path) = self.installed_path(&target_dir, &version)? {
+            return Ok(executable_path);
+        }
+
+        let (archive_filename, mut archive) = match self.cached(&version)? {
+            Some(cached) => cached,
+            None => {
+                let download_url = match download_url {
+                    Some(url) => url,
+                    None => self.download_url_async(&version).await?,
+                };
+                let archive_filename = archive_filename(&download_url).to_string();
+                let archive = self
+                    .download_async(&download_url, &archive_filename, &version)
+                    .await?;
+                (archive_filename, archive)
+            }
+        };
+
+        let executable_path = self.unpack(
+            &archive_filename,
+            archive.file(),
+            &target_dir,
+            Some(&version),
+        )?;
+        archive.cache()?;
+        Ok(executable_path)
+    }
+
+    /// Returns the released versions of the driver, oldest first
+    ///
+    /// With `platform_only`, only the versions with a download for the target platform
+    /// are returned. Listing `msedgedriver` versions isn't supported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> webdriver_install::Result<()> {
+    /// use webdriver_install::{Driver, Installer};
+    ///
+    /// for version in Installer::new(Driver::Gecko).available_versions(true)? {
+    ///     println!("{}", version);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn available_versions(&self, platform_only: bool) -> Result<Vec<String>> {
+        match self.driver {
+            Driver::Gecko => self.geckodriver().available_versions(platform_only),
+            Driver::Chrome => self.chromedriver().available_versions(platform_only),
+            Driver::Edge => self.edgedriver().available_versions(platform_only),
+        }
+    }
+
+    /// Async variant of [`Installer::available_versions`]
+    #[cfg(feature = "async")]
+    pub async fn available_versions_async(&self, platform_only: bool) -> Result<Vec<String>> {
+        match self.driver {
+            Driver::Gecko => {
+                self.geckodriver()
+                    .available_versions_async(platform_only)
+                    .await
+            }
+            Driver::Chrome => {
+                self.chromedriver()
+                    .available_versions_async(platform_only)
+                    .await
+            }
+            Driver::Edge => {
+                self.edgedriver()
+                    .available_versions_async(platform_only)
+                    .await
+            }
+        }
+    }
+
+    /// Locks the installation of the driver into `target_dir` against other processes
+    ///
+    /// Blocks until the lock is acquired. It's released when the returned file is dropped.
+    fn lock(&self, target_dir: &Path) -> Result<File> {
+        lock(&target_dir.join(lock_file_name(&self.executable_name()?)))
+    }
+
+    /// Locks the installation like [`Installer::lock`], without blocking the async runtime
+    #[cfg(feature = "async")]
+    async fn lock_async(&self, target_dir: &Path) -> Result<File> {
+        lock_async(&target_dir.join(lock_file_name(&self.executable_name()?))).await
+    }
+
+    /// Returns the explicitly requested version, in the format used by the driver releases
+    fn requested_version(&self) -> Option<String> {
+        self.version
+            .as_ref()
+            .map(|version| self.driver.normalize_version(version))
+    }
+
+    /// Returns the path of the driver executable, if `version` is installed already
+    fn installed_path(&self, target_dir: &Path, version: &str) -> Result<Option<PathBuf>> {
+        let executable = self.executable_name()?;
+        if self.force || installed_version(target_dir, &executable)?.as_deref() != Some(version) {
+            return Ok(None);
+        }
+        debug!("{} {} is installed already", self.driver.as_str(), version);
+        Ok(Some(target_dir.join(executable)))
+    }
+
+    fn download_url(&self, version: &str) -> Result<Url> {
+        match self.driver {
+            Driver::Gecko => self.geckodriver().download_url(version),
+            Driver::Chrome => self.chromedriver().download_url(version),
+            Driver::Edge => self.edgedriver().direct_download_url(version),
+        }
+    }
+
+    /// Async variant of [`Installer::download_url`]
+    #[cfg(feature = "async")]
+    async fn download_url_async(&self, version: &str) -> Result<Url> {
+        match self.driver {
+            Driver::Gecko => self.geckodriver().download_url_async(version).await,
+            Driver::Chrome => self.chromedriver().download_url_async(version).await,
+            _ => self.download_url(version),
+        }
+    }
+
+    /// Downloads the archive
+    ///
+    /// The response is streamed to a temporary file, which is only moved into the cache
+    /// by [`DownloadedArchive::cache`], once the archive is verified and unpacked.
+    fn download(
+        &self,
+        url: &Url,
+        archive_filename: &str,
+        version: &str,
+    ) -> Result<DownloadedArchive> {
+        let cached = self.cached_archive(archive_filename, version)?;
+
+        let tmp_file = self.http.download(
+            url.as_str(),
+            || download_file(&cached),
+            |downloaded, total| self.report_progress(downloaded, total),
+        )?;
+
+        finish_download(tmp_file, cached)
+    }
+
+    /// Async variant of [`Installer::download`]
+    #[cfg(feature = "async")]
+    async fn download_async(
+        &self,
+        url: &Url,
+        archive_filename: &str,
+        version: &str,
+    ) -> Result<DownloadedArchive> {
+        let cached = self.cached_archive(archive_filename, version)?;
+
+        let tmp_file = self
+            .http
+            .download_async(
+                url.as_str(),
+                || download_file(&cached),
+                |downloaded, total| self.report_progress(downloaded, total),
+            )
+            .await?;
+
+        finish_download(tmp_file, cached)
+    }
+
+    fn report_progress(&self, downloaded: u64, total: Option<u64>) {
+        if let Some(ProgressHook(progress)) = &self.progress {
+            progress(downloaded, total);
+        }
+    }
+
+    /// Opens the archive of `version` from the cache, if it was downloaded before
+    ///
+    /// Returns the archive file name along with the archive. The archive is looked up
+    /// before its download url is resolved, so that no request is sent for it.
+    fn cached(&self, version: &str) -> Result<Option<(String, DownloadedArchive)>> {
+        let cache = match self.open_cache()? {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+        let platform = self.target_platform()?.to_string();
+        let path = match cache.find(self.driver, version, &platform)? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        debug!("using cached archive {:?}", path);
+        let archive_filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Some((
+            archive_filename,
+            DownloadedArchive::Cached(File::open(path)?),
+        )))
+    }
+
+    /// Returns the cache and the path the archive is cached at, unless caching is disabled
+    fn cached_archive(
+        &self,
+        archive_filename: &str,
+        version: &str,
+    ) -> Result<Option<(Cache, PathBuf)>> {
+        let cache = match self.open_cache()? {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+        let platform = self.target_platform()?.to_string();
+        let path = cache.archive_path(self.driver, version, &platform, archive_filename);
+        Ok(Some((cache, path)))
+    }
+
+    /// Returns the cache to use, unless caching is disabled
+    fn open_cache(&self) -> Result<Option<Cache>> {
+        Ok(match (self.cache, &self.cache_dir) {
+            (false, _) => None,
+            (true, Some(dir)) => Some(Cache::at(dir)),
+            (true, None) => Some(Cache::new()?),
+        })
+    }
+
+    /// Updates the driver installed in `dir`, if it is outdated
+    ///
+    /// See [`Driver::update_in`].
+    pub fn update_in(&self, dir: PathBuf) -> Result<Update> {
+        let executable = self.executable_name()?;
+        if !dir.join(&executable).exists() {
+            return Ok(Update::NotInstalled);
+        }
+
+        let installed = installed_version(&dir, &executable)?;
+        let (version, download_url) = self.default_release()?;
+        if let Some(installed) = &installed {
+            if version_key(installed) >= version_key(&version) {
+                return Ok(Update::UpToDate(installed.clone()));
+            }
+        }
+
+        self.install_release(dir, version.clone(), download_url)?;
+        Ok(Update::Updated {
+            from: installed,
+            to: version,
+        })
+    }
+
+    /// Returns the version that is installed if none is requested explicitly, and its
+    /// download url if it was found while resolving the version
+    ///
+    /// The browser installed on the host is irrelevant for other platforms, so
+    /// the latest version is installed for them, as it is for an explicit channel.
+    fn default_release(&self) -> Result<(String, Option<Url>)> {
+        let for_host = self.is_for_host();
+        let version = match self.driver {
+            Driver::Gecko => self.geckodriver().latest_version(),
+            Driver::Chrome if for_host && self.channel.is_none() => {
+                return self.chromedriver().matching_release();
+            }
+            Driver::Chrome => self.chromedriver().latest_version(),
+            Driver::Edge if for_host => self.edgedriver().matching_version(),
+            Driver::Edge => self.edgedriver().latest_version(),
+        };
+        Ok((version?, None))
+    }
+
+    /// Async variant of [`Installer::default_release`]
+    #[cfg(feature = "async")]
+    async fn default_release_async(&self) -> Result<(String, Option<Url>)> {
+        let for_host = self.is_for_host();
+        let version = match self.driver {
+            Driver::Gecko => self.geckodriver().latest_version_async().await,
+            Driver::Chrome if for_host && self.channel.is_none() => {
+                return self.chromedriver().matching_release_async().await;
+            }
+            Driver::Chrome => self.chromedriver().latest_version_async().await,
+            Driver::Edge if for_host => self.edgedriver().matching_version_async().await,
+            Driver::Edge => self.edgedriver().latest_version_async().await,
+        };
+        Ok((version?, None))
+    }
+
+    /// Returns whether the driver is installed for the host platform
+    fn is_for_host(&self) -> bool {
+        self.platform
+            .map_or(true, |platform| Platform::host().ok() == Some(platform))
+    }
+
+    /// Returns the platform to install the driver for
+    fn target_platform(&self) -> Result<Platform> {
+        self.platform.map_or_else(Platform::host, Ok)
+    }
+
+    /// Returns the file name of the driver executable on the target platform
+    fn executable_name(&self) -> Result<String> {
+        Ok(self.driver.executable_name_for(self.target_platform()?.os))
+    }
+
+    fn chromedriver(&self) -> Chromedriver {
+        let mut chromedriver = Chromedriver::new().http_client(self.http.clone());
+        if let Some(platform) = self.platform {
+            chromedriver = chromedriver.platform(platform);
+        }
+        if let Some(url) = &self.mirror {
+            chromedriver = chromedriver.mirror(url);
+        }
+        if let Some(url) = &self.versions_mirror {
+            chromedriver = chromedriver.versions_mirror(url);
+        }
+        if let Some(url) = &self.legacy_mirror {
+            chromedriver = chromedriver.legacy_mirror(url);
+        }
+        if let Some(channel) = self.channel {
+            chromedriver = chromedriver.channel(channel);
+        }
+        chromedriver
+    }
+
+    fn geckodriver(&self) -> Geckodriver {
+        let mut geckodriver = Geckodriver::new().http_client(self.http.clone());
+        if let Some(platform) = self.platform {
+            geckodriver = geckodriver.platform(platform);
+        }
+        if let Some(url) = &self.mirror {
+            geckodriver = geckodriver.mirror(url);
+        }
+        if let Some(url) = &self.versions_mirror {
+            geckodriver = geckodriver.api_mirror(url);
+        }
+        if let Some(Secret(token)) = &self.github_token {
+            geckodriver = geckodriver.token(token);
+        }
+        geckodriver
+    }
+
+    fn edgedriver(&self) -> Edgedriver {
+        let mut edgedriver = Edgedriver::new().http_client(self.http.clone());
+        if let Some(platform) = self.platform {
+            edgedriver = edgedriver.platform(platform);
+        }
+        if let Some(url) = &self.mirror {
+            edgedriver = edgedriver.mirror(url);
+        }
+        edgedriver
+    }
+
+    /// Installs the driver from the local archive at `archive_path`
+    ///
+    /// The installation into `target_dir` must be locked already.
+    fn install_archive(&self, archive_path: &Path, target_dir: PathBuf) -> Result<PathBuf> {
+        let version = self.requested_version();
+        if let Some(version) = &version {
+            if let Some(executable_path) = self.installed_path(&target_dir, version)? {
+                return Ok(executable_path);
+            }
+        }
+
+        let archive_filename = archive_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| Error::Archive(format!("invalid archive path {:?}", archive_path)))?;
+        let mut archive = File::open(archive_path)
+            .map_err(|e| Error::Archive(format!("Failed to open {:?}: {}", archive_path, e)))?;
+
+        self.unpack(
+            archive_filename,
+            &mut archive,
+            &target_dir,
+            version.as_deref(),
+        )
+    }
+
+    /// Extracts the driver executable from the archive and records its `version`
+    fn unpack(
+        &self,
+        archive_filename: &str,
+        archive: &mut File,
+        target_dir: &Path,
+        version: Option<&str>,
+    ) -> Result<PathBuf> {
+        self.verify_checksum(archive_filename, archive)?;
+
+        let driver = self.driver;
+        let executables: Vec<&str> = driver.executable_names().collect();
+        let (tmp_file, executable) =
+            decompress(archive_filename, archive, target_dir, &executables)?;
+
+        // Make sure the extracted file will be executable.
+        //
+        // Windows doesn't need that, because all `.exe` files are automatically executable.
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        if self.target_platform()?.os != Os::Windows {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(tmp_file.path(), fs::Permissions::from_mode(0o775))?;
+        }
+
+        // Replace the executable with a rename, so that other processes either see the
+        // previous or the new executable, but never a partially written one
+        let executable_path = target_dir.join(&executable);
+        tmp_file
+            .persist(&executable_path)
+            .map_err(|e| Error::Io(e.error))?;
+
+        let metadata_path = target_dir.join(metadata_file_name(&executable));
+        match version {
+            Some(version) => fs::write(metadata_path, version)?,
+            // Don't leave the version of a previous installation behind
+            None if metadata_path.exists() => fs::remove_file(metadata_path)?,
+            None => {}
+        }
+
+        debug!("stored at {:?}", executable_path);
+        Ok(executable_path)
+    }
+
+    /// Compares the SHA-256 digest of the archive with the expected one, if any
+    ///
+    /// The archive is read from the start and rewound afterwards.
+    fn verify_checksum(&self, archive_filename: &str, archive: &mut File) -> Result<()> {
+        let expected = match (&self.sha256, &self.checksum_file) {
+            (Some(digest), _) => digest.trim().to_lowercase(),
+            (None, Some(path)) => checksum_for(&fs::read_to_string(path)?, archive_filename)
+                .ok_or_else(|| Error::ChecksumNotFound {
+                    archive: archive_filename.to_string(),
+                    checksum_file: path.clone(),
+                })?,
+            (None, None) => return Ok(()),
+        };
+
+        let mut hasher = Sha256::new();
+        archive.rewind()?;
+        io::copy(archive, &mut hasher)?;
+        archive.rewind()?;
+        let actual = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        if actual != expected {
+            return Err(Error::ChecksumMismatch {
+                archive: archive_filename.to_string(),
+                expected,
+                actual,
+            });
+        }
+        debug!("verified SHA-256 checksum of {}", archive_filename);
+        Ok(())
+    }
+}
+
+/// Creates the temporary file an archive is downloaded to
+///
+/// It's created next to the `cached` path, so that it can be moved there afterwards.
+fn download_file(cached: &Option<(Cache, PathBuf)>) -> Result<NamedTempFile> {
+    Ok(match cached {
+        Some((cache, path)) => cache.temp_file(path)?,
+        None => NamedTempFile::new()?,
+    })
+}
+
+/// Returns the completely downloaded archive ready for reading
+fn finish_download(
+    mut tmp_file: NamedTempFile,
+    cached: Option<(Cache, PathBuf)>,
+) -> Result<DownloadedArchive> {
+    tmp_file.rewind()?;
+    Ok(DownloadedArchive::Downloaded(tmp_file, cached))
+}
+
+/// An archive opened from the cache, or downloaded to a temporary file
+enum DownloadedArchive {
+    Cached(File),
+    /// The temporary file, and the cache and path to move it to, unless caching is disabled
+    Downloaded(NamedTempFile, Option<(Cache, PathBuf)>),
+}
+
+impl DownloadedArchive {
+    fn file(&mut self) -> &mut File {
+        match self {
+            Self::Cached(file) => file,
+            Self::Downloaded(tmp_file, _) => tmp_file.as_file_mut(),
+        }
+    }
+
+    /// Moves a downloaded archive into the cache
+    ///
+    /// Only called once the archive is unpacked, so that corrupt or tampered downloads
+    /// are never cached. They are removed with the temporary file instead.
+    fn cache(self) -> Result<()> {
+        if let Self::Downloaded(tmp_file, Some((cache, path))) = self {
+            cache.store(tmp_file, &path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the file name of the archive at `url`
+fn archive_filename(url: &Url) -> &str {
+    url.path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("tmp.bin")
+}
+
+/// Returns the digest listed for `archive_filename` in a `sha256sum` formatted file
+fn checksum_for(content: &str, archive_filename: &str) -> Option<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?, parts.next()?))
+        })
+        .find(|(_, name)| {
+            // `sha256sum --binary` prefixes the file name with `*`
+            let name = Path::new(name.trim_start_matches('*'));
+            name.file_name().and_then(|n| n.to_str()) == Some(archive_filename)
+        })
+        .map(|(digest, _)| digest.to_lowercase())
+}
+
+/// Returns the version recorded for `executable` in `dir`
+fn installed_version(dir: &Path, executable: &str) -> Result<Option<String>> {
+    let metadata_path = dir.join(metadata_file_name(executable));
+    if !metadata_path.exists() || !dir.join(executable).exists() {
+        return Ok(None);
+    }
+    let version = fs::read_to_string(metadata_path)?;
+    Ok(Some(version.trim().to_string()))
+}
+
+/// Locks the file at `path` against other processes, creating it if it doesn't exist
+///
+/// Blocks until the lock is acquired. It's released when the returned file is dropped.
+fn lock(path: &Path) -> Result<File> {
+    loop {
+        let file = open_lock_file(path)?;
+        match FileExt::try_lock(&file) {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                debug!("waiting for the installation lock {:?}", path);
+                FileExt::lock(&file)?;
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        if is_same_file(&file, path)? {
+            return Ok(file);
+        }
+        debug!(
+            "{:?} was removed while waiting for it, locking it again",
+            path
+        );
+    }
+}
+
+/// Locks the file at `path` like [`lock`], without blocking the async runtime
+///
+/// The lock is polled for, so installations waiting for each other on a single
+/// threaded runtime don't deadlock.
+#[cfg(feature = "async")]
+async fn lock_async(path: &Path) -> Result<File> {
+    let mut waiting = false;
+    loop {
+        let file = open_lock_file(path)?;
+        match FileExt::try_lock(&file) {
+            Ok(()) if is_same_file(&file, path)? => return Ok(file),
+            Ok(()) => debug!(
+                "{:?} was removed while waiting for it, locking it again",
+                path
+            ),
+            Err(TryLockError::WouldBlock) => {
+                if !waiting {
+                    debug!("waiting for the installation lock {:?}", path);
+                    waiting = true;
+                }
+                drop(file);
+                tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+/// Opens the lock file at `path`, creating it if it doesn't exist
+fn open_lock_file(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?)
+}
+
+/// Returns whether `file` is still the file at `path`
+///
+/// Lock files are only removed on unix, see [`Driver::uninstall_from`], so elsewhere
+/// the locked file is always the one at `path`.
+fn is_same_file(file: &File, path: &Path) -> Result<bool> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let locked = file.metadata()?;
+        Ok(locked.dev() == metadata.dev() && locked.ino() == metadata.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (file, metadata);
+        Ok(true)
+    }
+}
+
+/// Returns the name of the lock file serializing installations of `executable`
+fn lock_file_name(executable: &str) -> String {
+    format!(".{}.lock", executable)
+}
+
+/// Returns the name of the file recording the installed version of `executable`
+fn metadata_file_name(executable: &str) -> String {
+    format!("{}.version", executable)
+}
+
+/// Returns the default installation directory, `$HOME/.webdrivers`
+///
+/// The directory is created if it doesn't exist yet.
+pub fn default_dir() -> Result<PathBuf> {
+    let target_dir = home_dir()
+        .ok_or(Error::DirectoryNotFound("home"))?
+        .join(".webdrivers");
+    fs::create_dir_all(&target_dir)?;
+    Ok(target_dir)
+}
+
+/// Extracts the first file named like one of the `executables` from the archive
+///
+/// The executable is streamed from the archive to a temporary file in `target_dir`,
+/// without buffering it in memory. Returns the temporary file and the executable name.
+fn decompress<R: Read + Seek>(
+    archive_filename: &str,
+    archive: R,
+    target_dir: &Path,
+    executables: &[&str],
+) -> Result<(NamedTempFile, String)> {
     match archive_filename {
         name if name.ends_with("tar.gz") => {
-            let tar = GzDecoder::new(Cursor::new(bytes));
+            let tar = GzDecoder::new(archive);
             let mut archive = Archive::new(tar);
 
-            let driver_executable = archive.entries()?.filter_map(Result::ok).filter(|e| {
-                let filename = e.path().unwrap();
-                debug!("filename: {:?}", filename);
-                DRIVER_EXECUTABLES.contains(&filename.as_os_str().to_str().unwrap())
-            });
-
-            for mut exec in driver_executable {
-                let final_path = target_dir.join(exec.path()?);
-                exec.unpack(&final_path)?;
+            let entries = archive
+                .entries()
+                .map_err(|e| archive_error(archive_filename, e))?;
+            for mut entry in entries.filter_map(std::result::Result::ok) {
+                let path = entry.path()?.into_owned();
+                debug!("filename: {:?}", path);
+                if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
+                    if executables.contains(&file_name) {
+                        let mut tmp_file = NamedTempFile::new_in(target_dir)?;
+                        io::copy(&mut entry, &mut tmp_file)?;
 
-                return Ok(final_path);
+                        return Ok((tmp_file, file_name.to_string()));
+                    }
+                }
             }
         }
         name if name.ends_with("zip") => {
             debug!("zip file name: {}", name);
-            let mut zip = zip::ZipArchive::new(Cursor::new(bytes))?;
+            let mut zip =
+                zip::ZipArchive::new(archive).map_err(|e| archive_error(archive_filename, e))?;
 
-            let mut zip_bytes: Vec<u8> = vec![];
-            let mut filename: Option<String> = None;
             for i in 0..zip.len() {
-                let mut file = zip.by_index(i)?;
-                if let Some(file_name) = std::path::Path::new(file.name()).file_name().and_then(|n| n.to_str()){
-                    if DRIVER_EXECUTABLES.contains(&file_name) {
-                        filename = Some(file_name.to_string());
-                        file.read_to_end(&mut zip_bytes)?;
-                        break;
-                    }
-                }
-            }
-            if let Some(name) = filename {
-                debug!("saving zip file: {}", name);
-                let executable_path = target_dir.join(name);
-                let mut f = File::create(&executable_path)?;
-                std::io::copy(&mut zip_bytes.as_slice(), &mut f)?;
+                let mut file = zip
+                    .by_index(i)
+                    .map_err(|e| archive_error(archive_filename, e))?;
+                let file_name = match Path::new(file.name()).file_name().and_then(|n| n.to_str()) {
+                    Some(file_name) if executables.contains(&file_name) => file_name.to_string(),
+                    _ => continue,
+                };
+                debug!("saving zip file: {}", file_name);
+                let mut tmp_file = NamedTempFile::new_in(target_dir)?;
+                io::copy(&mut file, &mut tmp_file)?;
 
-                return Ok(executable_path);
+                return Ok((tmp_file, file_name));
             }
         }
 
-        ext => return Err(eyre!("No support for unarchiving {}, yet", ext)),
+        ext => {
+            return Err(Error::Archive(format!(
+                "No support for unarchiving {}, yet",
+                ext
+            )))
+        }
     }
-    Err(eyre!(
-        "This installer code should be unreachable! archive_filename: {}",
+    Err(Error::Archive(format!(
+        "Could not find {} in {}",
+        executables.join(" or "),
         archive_filename
-    ))
+    )))
+}
+
+fn archive_error(archive_filename: &str, error: impl fmt::Display) -> Error {
+    Error::Archive(format!("Failed to read {}: {}", archive_filename, error))
 }
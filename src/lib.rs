@@ -5,7 +5,7 @@
 //! By default, driver executables are installed into `$HOME/.webdrivers`.
 //!
 //! ```no_run
-//! # fn main() -> eyre::Result<()> {
+//! # fn main() -> webdriver_install::Result<()> {
 //! use webdriver_install::Driver;
 //!
 //! // Install geckodriver into $HOME/.webdrivers
@@ -13,6 +13,9 @@
 //!
 //! // Install chromedriver into $HOME/.webdrivers
 //! Driver::Chrome.install()?;
+//!
+//! // Install msedgedriver into $HOME/.webdrivers
+//! Driver::Edge.install()?;
 //! # Ok(())
 //! # }
 //! ```
@@ -20,7 +23,7 @@
 //! You can specify a different location with [`Driver::install_into`]:
 //!
 //! ```no_run
-//! # fn main() -> eyre::Result<()> {
+//! # fn main() -> webdriver_install::Result<()> {
 //! use webdriver_install::Driver;
 //! use std::path::PathBuf;
 //!
@@ -32,22 +35,89 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Errors
+//!
+//! All functions return an [`Error`], which tells apart the different causes of failure:
+//!
+//! ```no_run
+//! use webdriver_install::{Driver, Error};
+//!
+//! match Driver::Chrome.install() {
+//!     Ok(path) => println!("installed {}", path.display()),
+//!     Err(Error::UnsupportedPlatform { .. }) => println!("no chromedriver for this machine"),
+//!     Err(Error::Download(e)) => println!("download failed: {}", e),
+//!     Err(e) => println!("{}", e),
+//! }
+//! ```
+//!
+//! ## Mirrors
+//!
+//! Drivers can be downloaded from a mirror instead of the official servers, either
+//! with [`Installer::mirror`] or with one of these environment variables:
+//!
+//! * `WEBDRIVER_INSTALL_CHROMEDRIVER_MIRROR`, mirroring
+//!   `https://storage.googleapis.com/chrome-for-testing-public`
+//! * `WEBDRIVER_INSTALL_CHROMEDRIVER_VERSIONS_MIRROR`, mirroring
+//!   `https://googlechromelabs.github.io/chrome-for-testing`
+//! * `WEBDRIVER_INSTALL_CHROMEDRIVER_LEGACY_MIRROR`, mirroring
+//!   `https://chromedriver.storage.googleapis.com` for Chrome before version 115
+//! * `WEBDRIVER_INSTALL_GECKODRIVER_MIRROR`, mirroring
+//!   `https://github.com/mozilla/geckodriver/releases`
+//! * `WEBDRIVER_INSTALL_GECKODRIVER_API_MIRROR`, mirroring
+//!   `https://api.github.com/repos/mozilla/geckodriver`
+//! * `WEBDRIVER_INSTALL_EDGEDRIVER_MIRROR`, mirroring
+//!   `https://msedgedriver.microsoft.com`
 
+pub mod cache;
 mod chromedriver;
+mod edgedriver;
+pub mod error;
 mod geckodriver;
+pub mod http;
 pub mod installer;
+pub mod platform;
 
-use eyre::Result;
-pub use installer::Driver;
+pub use chromedriver::Channel;
+pub use error::{Error, Result};
+pub use installer::{Driver, Installer};
 use url::Url;
 
 #[doc(hidden)]
 pub trait DriverFetcher {
     const BASE_URL: &'static str;
 
+    /// Environment variable overriding `BASE_URL`
+    const MIRROR_ENV: &'static str;
+
     fn latest_version(&self) -> Result<String>;
 
+    /// Async variant of [`DriverFetcher::latest_version`]
+    #[cfg(feature = "async")]
+    #[allow(async_fn_in_trait)]
+    async fn latest_version_async(&self) -> Result<String>;
+
     fn direct_download_url(&self, version: &str) -> Result<Url>;
+
+    /// Returns the released versions of the driver, oldest first
+    ///
+    /// With `platform_only`, only the versions with a download for the platform are returned.
+    fn available_versions(&self, platform_only: bool) -> Result<Vec<String>>;
+
+    /// Async variant of [`DriverFetcher::available_versions`]
+    #[cfg(feature = "async")]
+    #[allow(async_fn_in_trait)]
+    async fn available_versions_async(&self, platform_only: bool) -> Result<Vec<String>>;
+}
+
+/// Returns the URL from the environment variable `var`, or `default` if it isn't set
+fn mirror_url(var: &str, default: &str) -> String {
+    std::env::var(var)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| default.to_string())
+        .trim_end_matches('/')
+        .to_string()
 }
 
 #[must_use]
@@ -0,0 +1,167 @@
+//! Operating systems and CPU architectures drivers are installed for
+use crate::{Error, Result};
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Os {
+    Linux,
+    Mac,
+    Windows,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// 32-bit x86
+    X86,
+    /// 64-bit x86, also known as `x86_64` or `amd64`
+    X64,
+    /// 64-bit ARM, also known as `aarch64` or Apple Silicon
+    Arm64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Platform {
+    pub os: Os,
+    pub arch: Arch,
+}
+
+impl Platform {
+    pub fn new(os: Os, arch: Arch) -> Self {
+        Self { os, arch }
+    }
+
+    /// Returns the platform `webdriver-install` is running on
+    pub fn host() -> Result<Self> {
+        let os = match std::env::consts::OS {
+            "linux" => Os::Linux,
+            "macos" => Os::Mac,
+            "windows" => Os::Windows,
+            _ => return Err(Self::unsupported_host()),
+        };
+        let arch = match std::env::consts::ARCH {
+            "x86" => Arch::X86,
+            "x86_64" => Arch::X64,
+            "aarch64" => Arch::Arm64,
+            _ => return Err(Self::unsupported_host()),
+        };
+        Ok(Self::new(os, arch))
+    }
+
+    /// Returns the error for drivers that aren't released for this platform
+    pub(crate) fn unsupported(&self, driver: &str) -> Error {
+        Error::UnsupportedPlatform {
+            driver: driver.to_string(),
+            platform: self.to_string(),
+        }
+    }
+
+    fn unsupported_host() -> Error {
+        Error::UnsupportedPlatform {
+            driver: String::from("webdriver-install"),
+            platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        }
+    }
+}
+
+impl FromStr for Os {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "linux" => Ok(Self::Linux),
+            "mac" | "macos" => Ok(Self::Mac),
+            "win" | "windows" => Ok(Self::Windows),
+            _ => Err(Error::InvalidPlatform(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Arch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "x86" | "i686" | "32" => Ok(Self::X86),
+            "x64" | "x86_64" | "amd64" | "64" => Ok(Self::X64),
+            "arm64" | "aarch64" => Ok(Self::Arm64),
+            _ => Err(Error::InvalidPlatform(s.to_string())),
+        }
+    }
+}
+
+/// Parses platforms like `linux-x64`, `windows-x86` or `mac-arm64`
+///
+/// The common aliases `macos`, `win`, `x86_64`, `amd64` and `aarch64` are accepted,
+/// as well as the short forms `linux64`, `win32` and `mac64` used in download URLs.
+impl FromStr for Platform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidPlatform(s.to_string());
+        let (os, arch) = match s.split_once(['-', '_']) {
+            Some(parts) => parts,
+            None => {
+                let split = s.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+                s.split_at(split)
+            }
+        };
+        Ok(Self::new(
+            os.parse().map_err(|_| invalid())?,
+            arch.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl fmt::Display for Os {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Linux => "linux",
+            Self::Mac => "mac",
+            Self::Windows => "windows",
+        })
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::X86 => "x86",
+            Self::X64 => "x64",
+            Self::Arm64 => "arm64",
+        })
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.os, self.arch)
+    }
+}
+
+#[test]
+fn from_str_test() {
+    let platform = |s: &str| s.parse::<Platform>().unwrap();
+    assert_eq!(Platform::new(Os::Linux, Arch::X64), platform("linux-x64"));
+    assert_eq!(Platform::new(Os::Linux, Arch::X64), platform("linux64"));
+    assert_eq!(
+        Platform::new(Os::Linux, Arch::Arm64),
+        platform("linux-aarch64")
+    );
+    assert_eq!(Platform::new(Os::Mac, Arch::Arm64), platform("macos-arm64"));
+    assert_eq!(Platform::new(Os::Mac, Arch::X64), platform("mac64"));
+    assert_eq!(Platform::new(Os::Windows, Arch::X86), platform("win32"));
+    assert_eq!(
+        Platform::new(Os::Windows, Arch::X64),
+        platform("Windows-x86_64")
+    );
+    assert!("linux".parse::<Platform>().is_err());
+    assert!("solaris-x64".parse::<Platform>().is_err());
+}
+
+#[test]
+fn display_from_str_roundtrip_test() {
+    let platform = Platform::new(Os::Windows, Arch::Arm64);
+    assert_eq!(platform, platform.to_string().parse().unwrap());
+}
@@ -0,0 +1,100 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+pub enum Response {
+    Ok(Vec<u8>),
+    Redirect(String),
+    NotFound,
+    Unavailable,
+    /// Announces the whole body, but closes the connection after half of it
+    Truncated(Vec<u8>),
+}
+
+/// Starts a minimal HTTP server standing in for the driver download servers
+///
+/// `handler` is called with the path of every request. Returns the base URL of the server.
+pub fn serve(handler: impl Fn(&str) -> Response + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let path = request_line
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or("/")
+                .to_string();
+            // Skip the request headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let (status, headers, body, sent) = match handler(&path) {
+                Response::Ok(body) => {
+                    let len = body.len();
+                    ("200 OK", String::new(), body, len)
+                }
+                Response::Truncated(body) => {
+                    let len = body.len() / 2;
+                    ("200 OK", String::new(), body, len)
+                }
+                Response::Redirect(location) => (
+                    "302 Found",
+                    format!("Location: {}\r\n", location),
+                    vec![],
+                    0,
+                ),
+                Response::NotFound => ("404 Not Found", String::new(), vec![], 0),
+                Response::Unavailable => ("503 Service Unavailable", String::new(), vec![], 0),
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                headers,
+                body.len()
+            );
+            let _ = stream.write_all(&body[..sent]);
+        }
+    });
+
+    base_url
+}
+
+/// Returns a `.tar.gz` archive containing a single file
+pub fn tar_gz(file_name: &str, content: &[u8]) -> Vec<u8> {
+    let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+        vec![],
+        flate2::Compression::default(),
+    ));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive
+        .append_data(&mut header, file_name, content)
+        .unwrap();
+    archive.into_inner().unwrap().finish().unwrap()
+}
+
+/// Returns a `.zip` archive containing the given files
+pub fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+    for (file_name, content) in files {
+        zip.start_file(*file_name, zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
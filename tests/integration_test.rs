@@ -1,5 +1,12 @@
+mod common;
+
+use common::Response;
 use tempfile::tempdir;
-use webdriver_install::Driver;
+use webdriver_install::cache::Cache;
+use webdriver_install::http::HttpClient;
+use webdriver_install::installer::Update;
+use webdriver_install::platform::{Arch, Os, Platform};
+use webdriver_install::{Channel, Driver, Error, Installer};
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use std::process::Command;
@@ -9,7 +16,9 @@ use std::process::Command;
 fn chromedriver_install_test() {
     let target_dir = tempdir().unwrap();
     let executable_path = target_dir.path().join("chromedriver");
-    Driver::Chrome.install_into(target_dir.into_path()).unwrap();
+    Driver::Chrome
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
 
     let output = Command::new(executable_path)
         .arg("--version")
@@ -23,7 +32,25 @@ fn chromedriver_install_test() {
 fn geckodriver_install_test() {
     let target_dir = tempdir().unwrap();
     let executable_path = target_dir.path().join("geckodriver");
-    Driver::Gecko.install_into(target_dir.into_path()).unwrap();
+    Driver::Gecko
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    let output = Command::new(executable_path)
+        .arg("--version")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+#[test]
+fn edgedriver_install_test() {
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join("msedgedriver");
+    Driver::Edge
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
 
     let output = Command::new(executable_path)
         .arg("--version")
@@ -32,6 +59,45 @@ fn geckodriver_install_test() {
     assert!(output.status.success());
 }
 
+#[test]
+fn cli_driver_version_test() {
+    let cli = env!("CARGO_BIN_EXE_webdriver_install");
+
+    let output = std::process::Command::new(cli)
+        .arg("--version")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("webdriver-install "));
+
+    // The driver version can only be given for an installation
+    let output = std::process::Command::new(cli)
+        .args(["--driver-version", "0.33.0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--install"));
+}
+
+#[test]
+fn driver_from_str_test() {
+    assert_eq!(Some(Driver::Gecko), Driver::from_str("GeckoDriver"));
+    assert_eq!(Some(Driver::Chrome), Driver::from_str("chromedriver"));
+    assert_eq!(None, Driver::from_str("operadriver"));
+
+    let target_dir = tempdir().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_webdriver_install"))
+        .args(["--remove", "GeckoDriver", "--dir"])
+        .arg(target_dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
 #[cfg(target_os = "windows")]
 #[test]
 fn chromedriver_install_test_win() {
@@ -53,6 +119,1189 @@ fn geckodriver_install_test_win() {
     let executable_path = target_dir.path().join("geckodriver.exe");
 
     assert!(!executable_path.exists());
-    Driver::Gecko.install_into(target_dir.into_path()).unwrap();
+    Driver::Gecko
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert!(executable_path.exists());
+}
+
+#[cfg(target_os = "windows")]
+#[test]
+fn edgedriver_install_test_win() {
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join("msedgedriver.exe");
+
+    assert!(!executable_path.exists());
+    Driver::Edge
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
     assert!(executable_path.exists());
 }
+
+#[test]
+fn installed_version_is_not_downloaded_again_test() {
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join(Driver::Gecko.executable_name());
+    std::fs::write(&executable_path, "not a real geckodriver").unwrap();
+    std::fs::write(
+        target_dir
+            .path()
+            .join(format!("{}.version", Driver::Gecko.executable_name())),
+        "v0.33.0",
+    )
+    .unwrap();
+
+    assert_eq!(
+        Some(String::from("v0.33.0")),
+        Driver::Gecko.installed_version(target_dir.path()).unwrap()
+    );
+    // Would fail if it tried to download and unarchive a release.
+    let installed_path = Installer::new(Driver::Gecko)
+        .version("0.33.0")
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(executable_path, installed_path);
+    assert_eq!(
+        executable_path,
+        Driver::Gecko
+            .install_version("0.33.0", target_dir.path().to_path_buf())
+            .unwrap()
+    );
+}
+
+#[test]
+fn update_installs_latest_version_test() {
+    let mirror = common::serve(|path| {
+        let content: &[u8] = match path {
+            "/latest" => return Response::Redirect(String::from("/tag/v0.34.0")),
+            "/tag/v0.34.0" => return Response::Ok(vec![]),
+            path if path.starts_with("/download/v0.33.0/") => b"geckodriver 0.33.0",
+            path if path.starts_with("/download/v0.34.0/") => b"geckodriver 0.34.0",
+            _ => return Response::NotFound,
+        };
+        let executable = Driver::Gecko.executable_name();
+        if path.ends_with(".zip") {
+            Response::Ok(common::zip(&[(&executable, content)]))
+        } else {
+            Response::Ok(common::tar_gz(&executable, content))
+        }
+    });
+    let installer = Installer::new(Driver::Gecko).mirror(&mirror).cache(false);
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = installer
+        .clone()
+        .version("v0.33.0")
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(
+        Update::Updated {
+            from: Some(String::from("v0.33.0")),
+            to: String::from("v0.34.0")
+        },
+        installer
+            .update_in(target_dir.path().to_path_buf())
+            .unwrap()
+    );
+    assert_eq!(
+        b"geckodriver 0.34.0".to_vec(),
+        std::fs::read(&executable_path).unwrap()
+    );
+
+    assert_eq!(
+        Update::UpToDate(String::from("v0.34.0")),
+        installer
+            .update_in(target_dir.path().to_path_buf())
+            .unwrap()
+    );
+}
+
+#[test]
+fn update_keeps_newer_installed_version_test() {
+    let mirror = common::serve(|path| match path {
+        "/latest" => Response::Redirect(String::from("/tag/v0.34.0")),
+        "/tag/v0.34.0" => Response::Ok(vec![]),
+        _ => Response::NotFound,
+    });
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join(Driver::Gecko.executable_name());
+    std::fs::write(&executable_path, b"geckodriver 0.35.0").unwrap();
+    std::fs::write(
+        target_dir
+            .path()
+            .join(format!("{}.version", Driver::Gecko.executable_name())),
+        "v0.35.0",
+    )
+    .unwrap();
+
+    assert_eq!(
+        Update::UpToDate(String::from("v0.35.0")),
+        Installer::new(Driver::Gecko)
+            .mirror(&mirror)
+            .update_in(target_dir.path().to_path_buf())
+            .unwrap()
+    );
+    assert_eq!(
+        b"geckodriver 0.35.0".to_vec(),
+        std::fs::read(&executable_path).unwrap()
+    );
+}
+
+#[test]
+fn update_skips_drivers_that_are_not_installed_test() {
+    let target_dir = tempdir().unwrap();
+    assert_eq!(
+        Update::NotInstalled,
+        Driver::Chrome
+            .update_in(target_dir.path().to_path_buf())
+            .unwrap()
+    );
+    assert!(!target_dir
+        .path()
+        .join(Driver::Chrome.executable_name())
+        .exists());
+}
+
+#[test]
+fn uninstall_only_removes_driver_files_test() {
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join(Driver::Gecko.executable_name());
+    let metadata_path = target_dir
+        .path()
+        .join(format!("{}.version", Driver::Gecko.executable_name()));
+    let chromedriver_path = target_dir.path().join(Driver::Chrome.executable_name());
+    let unrelated_path = target_dir.path().join("geckodriver.log");
+    for path in [
+        &executable_path,
+        &metadata_path,
+        &chromedriver_path,
+        &unrelated_path,
+    ] {
+        std::fs::write(path, "").unwrap();
+    }
+
+    let removed = Driver::Gecko
+        .uninstall_from(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        vec![executable_path.clone(), metadata_path.clone()],
+        removed
+    );
+    assert!(!executable_path.exists());
+    assert!(!metadata_path.exists());
+    assert!(chromedriver_path.exists());
+    assert!(unrelated_path.exists());
+}
+
+#[test]
+fn install_from_tar_gz_archive_test() {
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.33.0-linux64.tar.gz");
+    let content = b"#!/bin/sh\n";
+    std::fs::write(
+        &archive_path,
+        common::tar_gz(&Driver::Gecko.executable_name(), content),
+    )
+    .unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .from_archive(&archive_path)
+        .version("0.33.0")
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(
+        target_dir.path().join(Driver::Gecko.executable_name()),
+        executable_path
+    );
+    assert_eq!(content.to_vec(), std::fs::read(&executable_path).unwrap());
+    assert_eq!(
+        Some(String::from("v0.33.0")),
+        Driver::Gecko.installed_version(target_dir.path()).unwrap()
+    );
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&executable_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(0o775, mode & 0o777);
+    }
+}
+
+#[test]
+fn install_from_zip_archive_test() {
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir.path().join("chromedriver-linux64.zip");
+    let executable = format!("chromedriver-linux64/{}", Driver::Chrome.executable_name());
+    std::fs::write(
+        &archive_path,
+        common::zip(&[
+            ("chromedriver-linux64/LICENSE.chromedriver", b"license"),
+            (&executable, b"chromedriver"),
+        ]),
+    )
+    .unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Chrome)
+        .from_archive(&archive_path)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(
+        b"chromedriver".to_vec(),
+        std::fs::read(&executable_path).unwrap()
+    );
+    assert_eq!(
+        None,
+        Driver::Chrome.installed_version(target_dir.path()).unwrap()
+    );
+    assert!(!target_dir.path().join("LICENSE.chromedriver").exists());
+}
+
+#[test]
+fn install_chromedriver_channel_test() {
+    let download = |version: &str| {
+        format!(
+            r#"{{"version": "{version}", "downloads": {{"chromedriver": [{{"platform": "linux64", "url": "https://storage.googleapis.com/chrome-for-testing-public/{version}/linux64/chromedriver-linux64.zip"}}]}}}}"#,
+            version = version
+        )
+    };
+    let last_known_good = format!(
+        r#"{{"channels": {{"Stable": {}, "Beta": {}}}}}"#,
+        download("118.0.5993.70"),
+        download("119.0.6045.21")
+    );
+    let latest_patch_versions = format!(
+        r#"{{"builds": {{"118.0.5993": {}, "119.0.6045": {}}}}}"#,
+        download("118.0.5993.70"),
+        download("119.0.6045.21")
+    );
+    let versions = common::serve(move |path| match path {
+        "/last-known-good-versions-with-downloads.json" => {
+            Response::Ok(last_known_good.clone().into_bytes())
+        }
+        "/latest-patch-versions-per-build-with-downloads.json" => {
+            Response::Ok(latest_patch_versions.clone().into_bytes())
+        }
+        _ => Response::NotFound,
+    });
+    let mirror = common::serve(|path| match path {
+        "/118.0.5993.70/linux64/chromedriver-linux64.zip"
+        | "/119.0.6045.21/linux64/chromedriver-linux64.zip" => Response::Ok(common::zip(&[(
+            "chromedriver-linux64/chromedriver",
+            b"chromedriver",
+        )])),
+        _ => Response::NotFound,
+    });
+    let installer = Installer::new(Driver::Chrome)
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror(mirror)
+        .versions_mirror(versions)
+        .cache(false);
+
+    let target_dir = tempdir().unwrap();
+    installer
+        .clone()
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        Some(String::from("118.0.5993.70")),
+        Driver::Chrome.installed_version(target_dir.path()).unwrap()
+    );
+
+    installer
+        .clone()
+        .channel(Channel::Beta)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        Some(String::from("119.0.6045.21")),
+        Driver::Chrome.installed_version(target_dir.path()).unwrap()
+    );
+
+    // Older patch versions aren't listed, their download url follows the storage's URL scheme
+    let err = installer
+        .version("119.0.6045.0")
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Download(e) if e.url().unwrap().path() == "/119.0.6045.0/linux64/chromedriver-linux64.zip"
+    ));
+}
+
+#[test]
+fn install_legacy_chromedriver_test() {
+    let mirror = common::serve(|path| match path {
+        "/114.0.5735.90/chromedriver_linux64.zip" => {
+            Response::Ok(common::zip(&[("chromedriver", b"chromedriver")]))
+        }
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    Installer::new(Driver::Chrome)
+        .version("114.0.5735.90")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .legacy_mirror(mirror)
+        .cache(false)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        Some(String::from("114.0.5735.90")),
+        Driver::Chrome.installed_version(target_dir.path()).unwrap()
+    );
+}
+
+#[test]
+fn install_from_archive_without_driver_test() {
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir.path().join("chromedriver-linux64.zip");
+    std::fs::write(
+        &archive_path,
+        common::zip(&[(&Driver::Gecko.executable_name(), b"geckodriver")]),
+    )
+    .unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let err = Installer::new(Driver::Chrome)
+        .from_archive(&archive_path)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::Archive(_)));
+    assert!(err.to_string().contains("Could not find chromedriver"));
+
+    let err = Installer::new(Driver::Chrome)
+        .from_archive(archive_dir.path().join("missing.zip"))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::Archive(_)));
+    assert!(err.to_string().contains("missing.zip"));
+}
+
+#[test]
+fn install_errors_test() {
+    let target_dir = tempdir().unwrap();
+    let err = Installer::new(Driver::Chrome)
+        .version("118.0.5993.70")
+        .platform(Platform::new(Os::Linux, Arch::Arm64))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::UnsupportedPlatform { .. }));
+    assert_eq!(
+        "chromedriver is not available for linux-arm64",
+        err.to_string()
+    );
+
+    let mirror = common::serve(|_| Response::NotFound);
+    let err = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .mirror(mirror)
+        .cache(false)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::Download(_)));
+
+    let err = Driver::Gecko
+        .install_into(target_dir.path().join("missing"))
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidDirectory(_)));
+}
+
+#[test]
+fn install_replaces_executable_test() {
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.33.0-linux64.tar.gz");
+    std::fs::write(
+        &archive_path,
+        common::tar_gz(&Driver::Gecko.executable_name(), b"new geckodriver"),
+    )
+    .unwrap();
+    let broken_archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.34.0-linux64.tar.gz");
+    std::fs::write(&broken_archive_path, b"not an archive").unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = target_dir.path().join(Driver::Gecko.executable_name());
+    std::fs::write(&executable_path, b"old geckodriver").unwrap();
+
+    // A failed installation leaves the previous executable alone
+    Installer::new(Driver::Gecko)
+        .from_archive(&broken_archive_path)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert_eq!(
+        b"old geckodriver".to_vec(),
+        std::fs::read(&executable_path).unwrap()
+    );
+
+    Installer::new(Driver::Gecko)
+        .from_archive(&archive_path)
+        .version("v0.33.0")
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        b"new geckodriver".to_vec(),
+        std::fs::read(&executable_path).unwrap()
+    );
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&executable_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(0o775, mode & 0o777);
+    }
+
+    // No temporary files are left behind
+    let mut files: Vec<_> = std::fs::read_dir(target_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        vec![
+            format!(".{}.lock", Driver::Gecko.executable_name()),
+            Driver::Gecko.executable_name(),
+            format!("{}.version", Driver::Gecko.executable_name())
+        ],
+        files
+    );
+
+    // Uninstalling removes the lock file as well, on unix
+    let removed = Driver::Gecko
+        .uninstall_from(target_dir.path().to_path_buf())
+        .unwrap();
+    let remaining = if cfg!(unix) { 0 } else { 1 };
+    assert_eq!(3 - remaining, removed.len());
+    assert_eq!(
+        remaining,
+        std::fs::read_dir(target_dir.path()).unwrap().count()
+    );
+}
+
+#[test]
+fn concurrent_installations_download_once_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let downloads = Arc::new(AtomicUsize::new(0));
+    let server_downloads = Arc::clone(&downloads);
+    let mirror = common::serve(move |path| match path {
+        path if path.starts_with("/download/v0.33.0/") => {
+            server_downloads.fetch_add(1, Ordering::SeqCst);
+            Response::Ok(common::tar_gz("geckodriver", b"geckodriver"))
+        }
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    let installers: Vec<_> = (0..4)
+        .map(|_| {
+            let installer = Installer::new(Driver::Gecko)
+                .version("v0.33.0")
+                .platform(Platform::new(Os::Linux, Arch::X64))
+                .mirror(mirror.clone())
+                .cache(false);
+            let target_dir = target_dir.path().to_path_buf();
+            std::thread::spawn(move || installer.install_into(target_dir).unwrap())
+        })
+        .collect();
+    for installer in installers {
+        installer.join().unwrap();
+    }
+
+    assert_eq!(1, downloads.load(Ordering::SeqCst));
+}
+
+#[test]
+fn install_from_mirror_test() {
+    let mirror = common::serve(|path| match path {
+        "/latest" => Response::Redirect(String::from("/tag/v0.33.0")),
+        "/tag/v0.33.0" => Response::Ok(vec![]),
+        path if path.starts_with("/download/v0.33.0/") && path.ends_with(".tar.gz") => {
+            Response::Ok(common::tar_gz(
+                &Driver::Gecko.executable_name(),
+                b"geckodriver",
+            ))
+        }
+        path if path.starts_with("/download/v0.33.0/") && path.ends_with(".zip") => Response::Ok(
+            common::zip(&[(&Driver::Gecko.executable_name(), b"geckodriver")]),
+        ),
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .mirror(mirror)
+        .cache(false)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(
+        b"geckodriver".to_vec(),
+        std::fs::read(executable_path).unwrap()
+    );
+    assert_eq!(
+        Some(String::from("v0.33.0")),
+        Driver::Gecko.installed_version(target_dir.path()).unwrap()
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn install_into_async_test() {
+    let mirror = common::serve(|path| match path {
+        "/latest" => Response::Redirect(String::from("/tag/v0.33.0")),
+        "/tag/v0.33.0" => Response::Ok(vec![]),
+        path if path.starts_with("/download/v0.33.0/") && path.ends_with(".tar.gz") => {
+            Response::Ok(common::tar_gz(
+                &Driver::Gecko.executable_name(),
+                b"geckodriver",
+            ))
+        }
+        path if path.starts_with("/download/v0.33.0/") && path.ends_with(".zip") => Response::Ok(
+            common::zip(&[(&Driver::Gecko.executable_name(), b"geckodriver")]),
+        ),
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .mirror(mirror)
+        .cache(false)
+        .install_into_async(target_dir.path().to_path_buf())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        b"geckodriver".to_vec(),
+        std::fs::read(executable_path).unwrap()
+    );
+    assert_eq!(
+        Some(String::from("v0.33.0")),
+        Driver::Gecko.installed_version(target_dir.path()).unwrap()
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn concurrent_async_installations_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let downloads = Arc::new(AtomicUsize::new(0));
+    let server_downloads = Arc::clone(&downloads);
+    let mirror = common::serve(move |path| match path {
+        "/download/v0.33.0/geckodriver-v0.33.0-linux64.tar.gz" => {
+            server_downloads.fetch_add(1, Ordering::SeqCst);
+            Response::Ok(common::tar_gz("geckodriver", b"geckodriver"))
+        }
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    let installer = Installer::new(Driver::Gecko)
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .version("v0.33.0")
+        .mirror(mirror)
+        .cache(false);
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.33.0-linux64.tar.gz");
+    std::fs::write(&archive_path, common::tar_gz("geckodriver", b"geckodriver")).unwrap();
+    let archive_installer = installer.clone().from_archive(&archive_path);
+    // The installations run on the same thread, waiting for the lock must not block it
+    let (first, second, third) = tokio::join!(
+        installer.install_into_async(target_dir.path().to_path_buf()),
+        installer.install_into_async(target_dir.path().to_path_buf()),
+        archive_installer.install_into_async(target_dir.path().to_path_buf())
+    );
+
+    let executable_path = first.unwrap();
+    assert_eq!(executable_path, second.unwrap());
+    assert_eq!(executable_path, third.unwrap());
+    assert_eq!(1, downloads.load(Ordering::SeqCst));
+}
+
+#[test]
+fn install_for_other_platform_test() {
+    let mirror = common::serve(|path| match path {
+        "/download/v0.33.0/geckodriver-v0.33.0-win32.zip" => {
+            Response::Ok(common::zip(&[("geckodriver.exe", b"geckodriver")]))
+        }
+        _ => Response::NotFound,
+    });
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Windows, Arch::X86))
+        .mirror(mirror)
+        .cache(false)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(target_dir.path().join("geckodriver.exe"), executable_path);
+    assert_eq!(
+        b"geckodriver".to_vec(),
+        std::fs::read(executable_path).unwrap()
+    );
+    assert!(target_dir.path().join("geckodriver.exe.version").exists());
+}
+
+#[test]
+fn install_with_progress_test() {
+    use std::sync::{Arc, Mutex};
+
+    let archive = common::tar_gz(&Driver::Gecko.executable_name(), b"geckodriver");
+    let archive_len = archive.len() as u64;
+    let mirror = common::serve(move |path| match path {
+        path if path.starts_with("/download/v0.33.0/") => Response::Ok(archive.clone()),
+        _ => Response::NotFound,
+    });
+
+    let reports = Arc::new(Mutex::new(vec![]));
+    let target_dir = tempdir().unwrap();
+    Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror(mirror)
+        .cache(false)
+        .on_progress({
+            let reports = Arc::clone(&reports);
+            move |downloaded, total| reports.lock().unwrap().push((downloaded, total))
+        })
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(Some(&(archive_len, Some(archive_len))), reports.last());
+    assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn install_retries_interrupted_download_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let server_requests = Arc::clone(&requests);
+    let mirror = common::serve(move |_| {
+        let archive = common::tar_gz("geckodriver", b"geckodriver");
+        if server_requests.fetch_add(1, Ordering::SeqCst) < 1 {
+            Response::Truncated(archive)
+        } else {
+            Response::Ok(archive)
+        }
+    });
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror(mirror)
+        .cache(false)
+        .http_client(HttpClient::new().retries(1).backoff(Duration::ZERO))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        b"geckodriver".to_vec(),
+        std::fs::read(executable_path).unwrap()
+    );
+    assert_eq!(2, requests.load(Ordering::SeqCst));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn install_async_retries_interrupted_download_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let server_requests = Arc::clone(&requests);
+    let mirror = common::serve(move |_| {
+        let archive = common::tar_gz("geckodriver", b"geckodriver");
+        if server_requests.fetch_add(1, Ordering::SeqCst) < 1 {
+            Response::Truncated(archive)
+        } else {
+            Response::Ok(archive)
+        }
+    });
+
+    let target_dir = tempdir().unwrap();
+    let executable_path = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror(mirror)
+        .cache(false)
+        .http_client(HttpClient::new().retries(1).backoff(Duration::ZERO))
+        .install_into_async(target_dir.path().to_path_buf())
+        .await
+        .unwrap();
+    assert_eq!(
+        b"geckodriver".to_vec(),
+        std::fs::read(executable_path).unwrap()
+    );
+    assert_eq!(2, requests.load(Ordering::SeqCst));
+}
+
+#[test]
+fn install_retries_unavailable_server_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let server_requests = Arc::clone(&requests);
+    let mirror = common::serve(move |_| {
+        if server_requests.fetch_add(1, Ordering::SeqCst) < 2 {
+            Response::Unavailable
+        } else {
+            Response::Ok(common::tar_gz("geckodriver", b"geckodriver"))
+        }
+    });
+
+    let installer = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror(mirror)
+        .cache(false);
+
+    let target_dir = tempdir().unwrap();
+    let err = installer
+        .clone()
+        .http_client(HttpClient::new().retries(1).backoff(Duration::ZERO))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::Download(_)));
+    assert_eq!(2, requests.load(Ordering::SeqCst));
+
+    requests.store(0, Ordering::SeqCst);
+    installer
+        .http_client(HttpClient::new().retries(2).backoff(Duration::ZERO))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(3, requests.load(Ordering::SeqCst));
+}
+
+#[test]
+fn install_geckodriver_with_github_api_test() {
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    let api_url = Arc::new(OnceLock::new());
+    let server_url = Arc::clone(&api_url);
+    let requested = Arc::new(Mutex::new(vec![]));
+    let server_requested = Arc::clone(&requested);
+    let api = common::serve(move |path| {
+        server_requested.lock().unwrap().push(path.to_string());
+        let release = |tag: &str| {
+            let asset = format!("geckodriver-{}-linux64.tar.gz", tag);
+            let body = format!(
+                r#"{{"tag_name": "{tag}", "assets": [{{"name": "{asset}", "browser_download_url": "{url}/assets/{asset}"}}]}}"#,
+                tag = tag,
+                asset = asset,
+                url = server_url.get().unwrap()
+            );
+            Response::Ok(body.into_bytes())
+        };
+        match path {
+            "/releases/latest" | "/releases/tags/v0.34.0" => release("v0.34.0"),
+            "/assets/geckodriver-v0.34.0-linux64.tar.gz" => {
+                Response::Ok(common::tar_gz("geckodriver", b"geckodriver"))
+            }
+            _ => Response::NotFound,
+        }
+    });
+    api_url.set(api.clone()).unwrap();
+
+    let target_dir = tempdir().unwrap();
+    Installer::new(Driver::Gecko)
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror("http://mirror.invalid")
+        .versions_mirror(api)
+        .github_token("secret")
+        .cache(false)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            "/releases/latest",
+            "/releases/tags/v0.34.0",
+            "/assets/geckodriver-v0.34.0-linux64.tar.gz"
+        ],
+        *requested.lock().unwrap()
+    );
+    assert_eq!(
+        Some(String::from("v0.34.0")),
+        Driver::Gecko.installed_version(target_dir.path()).unwrap()
+    );
+}
+
+/// Serves the version lists of chromedriver and geckodriver
+fn serve_versions() -> String {
+    common::serve(|path| {
+        match path {
+        "/known-good-versions-with-downloads.json" => Response::Ok(
+            br#"{"versions": [
+                {"version": "113.0.5672.0", "downloads": {"chrome": []}},
+                {"version": "115.0.5763.0", "downloads": {"chromedriver": [{"platform": "win64"}]}},
+                {"version": "118.0.5993.70", "downloads": {"chromedriver": [{"platform": "linux64"}]}}
+            ]}"#
+            .to_vec(),
+        ),
+        "/releases?per_page=100&page=1" => Response::Ok(
+            br#"[
+                {"tag_name": "v0.34.0", "assets": [{"name": "geckodriver-v0.34.0-linux64.tar.gz", "browser_download_url": "linux64.tar.gz"}]},
+                {"tag_name": "v0.33.0", "assets": [{"name": "geckodriver-v0.33.0-win64.zip", "browser_download_url": "win64.zip"}]}
+            ]"#
+            .to_vec(),
+        ),
+        _ => Response::NotFound,
+    }
+    })
+}
+
+#[test]
+fn available_versions_test() {
+    let server = serve_versions();
+    let installer = |driver| {
+        Installer::new(driver)
+            .platform(Platform::new(Os::Linux, Arch::X64))
+            .versions_mirror(server.as_str())
+    };
+
+    assert_eq!(
+        vec!["115.0.5763.0", "118.0.5993.70"],
+        installer(Driver::Chrome).available_versions(false).unwrap()
+    );
+    assert_eq!(
+        vec!["118.0.5993.70"],
+        installer(Driver::Chrome).available_versions(true).unwrap()
+    );
+    assert_eq!(
+        vec!["v0.33.0", "v0.34.0"],
+        installer(Driver::Gecko).available_versions(false).unwrap()
+    );
+    assert_eq!(
+        vec!["v0.34.0"],
+        installer(Driver::Gecko).available_versions(true).unwrap()
+    );
+    assert!(matches!(
+        installer(Driver::Edge).available_versions(false),
+        Err(Error::VersionNotFound(_))
+    ));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn available_versions_async_test() {
+    let server = serve_versions();
+    let installer = |driver| {
+        Installer::new(driver)
+            .platform(Platform::new(Os::Linux, Arch::X64))
+            .versions_mirror(server.as_str())
+    };
+
+    assert_eq!(
+        vec!["118.0.5993.70"],
+        installer(Driver::Chrome)
+            .available_versions_async(true)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        vec!["v0.33.0", "v0.34.0"],
+        installer(Driver::Gecko)
+            .available_versions_async(false)
+            .await
+            .unwrap()
+    );
+    assert!(matches!(
+        installer(Driver::Edge)
+            .available_versions_async(false)
+            .await,
+        Err(Error::VersionNotFound(_))
+    ));
+}
+
+#[test]
+fn install_through_proxy_test() {
+    use std::sync::{Arc, Mutex};
+
+    let requested = Arc::new(Mutex::new(vec![]));
+    let proxy_requested = Arc::clone(&requested);
+    let proxy = common::serve(move |path| {
+        proxy_requested.lock().unwrap().push(path.to_string());
+        Response::Ok(common::tar_gz("geckodriver", b"geckodriver"))
+    });
+
+    let installer = Installer::new(Driver::Gecko)
+        .version("v0.33.0")
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .mirror("http://mirror.invalid")
+        .cache(false);
+
+    let target_dir = tempdir().unwrap();
+    installer
+        .clone()
+        .http_client(HttpClient::new().proxy(proxy.as_str()))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+    assert_eq!(
+        vec!["http://mirror.invalid/download/v0.33.0/geckodriver-v0.33.0-linux64.tar.gz"],
+        *requested.lock().unwrap()
+    );
+
+    let err = installer
+        .clone()
+        .force(true)
+        .http_client(
+            HttpClient::new()
+                .proxy(proxy.as_str())
+                .no_proxy("mirror.invalid")
+                .retries(0),
+        )
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::Download(_)));
+    assert_eq!(1, requested.lock().unwrap().len());
+
+    let err = installer
+        .force(true)
+        .http_client(HttpClient::new().root_certificates("not a certificate"))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::HttpConfig(_)));
+}
+
+#[test]
+fn install_with_checksum_test() {
+    use sha2::{Digest, Sha256};
+
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.33.0-linux64.tar.gz");
+    let archive = common::tar_gz(&Driver::Gecko.executable_name(), b"geckodriver");
+    std::fs::write(&archive_path, &archive).unwrap();
+    let digest: String = Sha256::digest(&archive)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    let target_dir = tempdir().unwrap();
+    Installer::new(Driver::Gecko)
+        .from_archive(&archive_path)
+        .sha256(digest.to_uppercase())
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+
+    let checksum_file = archive_dir.path().join("SHA256SUMS");
+    std::fs::write(
+        &checksum_file,
+        format!(
+            "{}  other.zip\n{} *geckodriver-v0.33.0-linux64.tar.gz\n",
+            "0".repeat(64),
+            digest
+        ),
+    )
+    .unwrap();
+    Installer::new(Driver::Gecko)
+        .from_archive(&archive_path)
+        .checksum_file(&checksum_file)
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap();
+}
+
+#[test]
+fn install_with_checksum_mismatch_test() {
+    let archive_dir = tempdir().unwrap();
+    let archive_path = archive_dir
+        .path()
+        .join("geckodriver-v0.33.0-linux64.tar.gz");
+    std::fs::write(
+        &archive_path,
+        common::tar_gz(&Driver::Gecko.executable_name(), b"geckodriver"),
+    )
+    .unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let err = Installer::new(Driver::Gecko)
+        .from_archive(&archive_path)
+        .sha256("0".repeat(64))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+
+    let (archive, expected) = match err {
+        Error::ChecksumMismatch {
+            archive, expected, ..
+        } => (archive, expected),
+        err => panic!("unexpected error: {}", err),
+    };
+    assert_eq!("geckodriver-v0.33.0-linux64.tar.gz", archive);
+    assert_eq!("0".repeat(64), expected);
+    assert!(!target_dir
+        .path()
+        .join(Driver::Gecko.executable_name())
+        .exists());
+}
+
+#[test]
+fn install_cached_chromedriver_offline_test() {
+    use std::sync::{Arc, Mutex};
+
+    let requested = Arc::new(Mutex::new(vec![]));
+    let server_requested = Arc::clone(&requested);
+    let server = common::serve(move |path| {
+        server_requested.lock().unwrap().push(path.to_string());
+        match path {
+            "/latest-patch-versions-per-build-with-downloads.json" => Response::Ok(
+                br#"{"builds": {"118.0.5993": {"version": "118.0.5993.70", "downloads": {"chromedriver": [{"platform": "linux64", "url": "https://storage.googleapis.com/chrome-for-testing-public/118.0.5993.70/linux64/chromedriver-linux64.zip"}]}}}}"#
+                    .to_vec(),
+            ),
+            "/118.0.5993.70/linux64/chromedriver-linux64.zip" => Response::Ok(common::zip(&[(
+                "chromedriver-linux64/chromedriver",
+                b"chromedriver",
+            )])),
+            _ => Response::NotFound,
+        }
+    });
+    let cache_dir = tempdir().unwrap();
+    let installer = Installer::new(Driver::Chrome)
+        .platform(Platform::new(Os::Linux, Arch::X64))
+        .version("118.0.5993.70")
+        .mirror(&server)
+        .versions_mirror(&server)
+        .cache_dir(cache_dir.path());
+
+    for _ in 0..2 {
+        let target_dir = tempdir().unwrap();
+        installer
+            .clone()
+            .install_into(target_dir.path().to_path_buf())
+            .unwrap();
+        assert_eq!(
+            Some(String::from("118.0.5993.70")),
+            Driver::Chrome.installed_version(target_dir.path()).unwrap()
+        );
+    }
+
+    // The second installation is served from the cache without any request
+    assert_eq!(
+        vec![
+            "/latest-patch-versions-per-build-with-downloads.json",
+            "/118.0.5993.70/linux64/chromedriver-linux64.zip"
+        ],
+        *requested.lock().unwrap()
+    );
+}
+
+#[test]
+fn invalid_download_is_not_cached_test() {
+    let mirror = common::serve(|path| match path {
+        path if path.starts_with("/download/v0.33.0/") => {
+            let executable = Driver::Gecko.executable_name();
+            if path.ends_with(".zip") {
+                Response::Ok(common::zip(&[(&executable, b"geckodriver")]))
+            } else {
+                Response::Ok(common::tar_gz(&executable, b"geckodriver"))
+            }
+        }
+        path if path.starts_with("/download/v0.34.0/") => {
+            Response::Ok(b"<html>Not an archive</html>".to_vec())
+        }
+        _ => Response::NotFound,
+    });
+    let cache_dir = tempdir().unwrap();
+    let installer = Installer::new(Driver::Gecko)
+        .mirror(&mirror)
+        .cache_dir(cache_dir.path());
+
+    let target_dir = tempdir().unwrap();
+    let err = installer
+        .clone()
+        .version("v0.33.0")
+        .sha256("0".repeat(64))
+        .install_into(target_dir.path().to_path_buf())
+        .unwrap_err();
+    assert!(matches!(err, Error::ChecksumMismatch { .. }));
+    assert!(installer
+        .clone()
+        .version("v0.34.0")
+        .install_into(target_dir.path().to_path_buf())
+        .is_err());
+
+    assert!(Cache::at(cache_dir.path()).entries().unwrap().is_empty());
+    assert!(!target_dir
+        .path()
+        .join(Driver::Gecko.executable_name())
+        .exists());
+}
+
+#[test]
+fn install_from_cache_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let downloads = Arc::new(AtomicUsize::new(0));
+    let server_downloads = Arc::clone(&downloads);
+    let mirror = common::serve(move |path| match path {
+        path if path.starts_with("/download/") => {
+            server_downloads.fetch_add(1, Ordering::SeqCst);
+            let executable = Driver::Gecko.executable_name();
+            if path.ends_with(".zip") {
+                Response::Ok(common::zip(&[(&executable, b"geckodriver")]))
+            } else {
+                Response::Ok(common::tar_gz(&executable, b"geckodriver"))
+            }
+        }
+        _ => Response::NotFound,
+    });
+    let cache_dir = tempdir().unwrap();
+
+    for version in ["v0.32.0", "v0.33.0", "v0.33.0"] {
+        let target_dir = tempdir().unwrap();
+        Installer::new(Driver::Gecko)
+            .mirror(&mirror)
+            .cache_dir(cache_dir.path())
+            .version(version)
+            .install_into(target_dir.path().to_path_buf())
+            .unwrap();
+        assert!(target_dir
+            .path()
+            .join(Driver::Gecko.executable_name())
+            .exists());
+    }
+    assert_eq!(2, downloads.load(Ordering::SeqCst));
+
+    let cache = Cache::at(cache_dir.path());
+    let versions: Vec<String> = cache
+        .entries()
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.version)
+        .collect();
+    assert_eq!(vec!["v0.32.0", "v0.33.0"], versions);
+
+    let removed = cache.prune(1).unwrap();
+    assert_eq!(1, removed.len());
+    assert_eq!("v0.32.0", removed[0].version);
+    assert!(!removed[0].path.exists());
+    assert_eq!(1, cache.entries().unwrap().len());
+}